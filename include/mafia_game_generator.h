@@ -0,0 +1,106 @@
+#ifndef MAFIA_GAME_GENERATOR_H
+#define MAFIA_GAME_GENERATOR_H
+
+/* Файл сгенерирован cbindgen, не редактируйте его вручную. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Код результата вызова.
+typedef enum MggStatus {
+  // Успешно.
+  MGG_STATUS_OK = 0,
+  // Передан нулевой указатель или недопустимое значение аргумента.
+  MGG_STATUS_INVALID_ARGUMENT = 1,
+  // Количество игроков вне допустимого диапазона.
+  MGG_STATUS_INVALID_PLAYER_COUNT = 2,
+  // Неизвестный код роли.
+  MGG_STATUS_INVALID_ROLE = 3,
+  // Ошибка ввода/вывода.
+  MGG_STATUS_IO = 4,
+  // Неверный PIN-код.
+  MGG_STATUS_WRONG_PIN = 5,
+  // Ошибка шифрования.
+  MGG_STATUS_ENCRYPTION = 6,
+  // Прочие ошибки.
+  MGG_STATUS_INTERNAL = 255,
+} MggStatus;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Генерирует набор ролей (без перемешивания) для заданного количества игроков
+// (от `MIN_PLAYERS` до `MAX_PLAYERS`, то есть от 4 до 60).
+// `game_mode`: 0 — классический, 1 — расширенный.
+// Коды ролей: 0 — Мирный житель, 1 — Мафия, 2 — Дон, 3 — Шериф, 4 — Доктор, 5 — Маньяк.
+// Результат освобождается через `mgg_roles_free`.
+//
+// # Safety
+//
+// `out_roles` и `out_len` должны быть действительными указателями для записи.
+enum MggStatus mgg_get_roles_for_players(uint8_t player_count,
+                                         uint8_t game_mode,
+                                         uint8_t **out_roles,
+                                         size_t *out_len);
+
+// Освобождает массив ролей, полученный из `mgg_get_roles_for_players`.
+//
+// # Safety
+//
+// `roles` и `len` должны быть ровно теми, что вернула библиотека (или `roles` равен NULL).
+void mgg_roles_free(uint8_t *roles,
+                    size_t len);
+
+// Перемешивает массив ролей детерминированно по зерну `seed`.
+//
+// # Safety
+//
+// `roles` должен указывать на `len` доступных для записи байтов.
+enum MggStatus mgg_shuffle_roles(uint8_t *roles,
+                                 size_t len,
+                                 uint64_t seed);
+
+// Возвращает название роли. Строка освобождается через `mgg_string_free`.
+//
+// # Safety
+//
+// `out_name` должен быть действительным указателем для записи.
+enum MggStatus mgg_role_name(uint8_t role,
+                             char **out_name);
+
+// Возвращает описание роли. Строка освобождается через `mgg_string_free`.
+//
+// # Safety
+//
+// `out_description` должен быть действительным указателем для записи.
+enum MggStatus mgg_role_description(uint8_t role,
+                                    char **out_description);
+
+// Возвращает сторону роли: 0 — мирные жители, 1 — мафия, 2 — одиночка.
+//
+// # Safety
+//
+// `out_faction` должен быть действительным указателем для записи.
+enum MggStatus mgg_role_faction(uint8_t role,
+                                uint8_t *out_faction);
+
+// Возвращает текст последней ошибки в текущем потоке или NULL, если ошибок не было.
+// Строка освобождается через `mgg_string_free`.
+char *mgg_last_error_message(void);
+
+// Освобождает строку, полученную от библиотеки.
+//
+// # Safety
+//
+// `text` должен быть получен от библиотеки и ещё не освобождён (или равен NULL).
+void mgg_string_free(char *text);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MAFIA_GAME_GENERATOR_H */
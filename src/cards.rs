@@ -0,0 +1,351 @@
+use crate::game_setup::Assignment;
+use crate::role::Role;
+
+/// Формат файла с карточками для печати.
+#[derive(Debug, Copy, Clone)]
+pub enum CardFormat {
+	Html, // Одна HTML-страница, печатается из браузера
+	Svg,  // Отдельный SVG-файл на каждый лист A4
+}
+
+/// Настройки внешнего вида карточек.
+#[derive(Debug, Copy, Clone)]
+pub struct CardOptions {
+	pub format: CardFormat,
+	/// Складная карточка: имя снаружи, роль внутри.
+	pub folded: bool,
+	/// Показывать значок роли.
+	pub icons: bool,
+}
+
+// Размеры листа A4 и карточек в миллиметрах.
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const CARD_WIDTH: f32 = 90.0;
+const FLAT_CARD_HEIGHT: f32 = 60.0;
+/// Складная карточка состоит из трёх панелей: имя, роль и клапан, закрывающий роль.
+const FOLDED_PANEL_HEIGHT: f32 = 45.0;
+const GAP: f32 = 6.0;
+// Сколько строк помещается на одном листе ведущего в SVG (строка — 7 мм, поля сверху и снизу).
+const HOST_ROWS_PER_PAGE: usize = 36;
+
+/// Положение карточки на листе.
+struct Placement<'a> {
+	x: f32,
+	y: f32,
+	player: &'a str,
+	role: Role,
+	/// Известные игроку союзники (пустая строка, если их нет).
+	teammates: String,
+}
+
+/**
+ * Возвращает размеры карточки в выбранном варианте.
+ */
+fn card_size(options: &CardOptions) -> (f32, f32) {
+	if options.folded {
+		(CARD_WIDTH, FOLDED_PANEL_HEIGHT * 3.0)
+	} else {
+		(CARD_WIDTH, FLAT_CARD_HEIGHT)
+	}
+}
+
+/**
+ * Раскладывает карточки по листам A4: сетка по центру листа, слева направо и сверху вниз.
+ */
+fn layout<'a>(assignment: &'a Assignment, options: &CardOptions) -> Vec<Vec<Placement<'a>>> {
+	let (width, height) = card_size(options);
+	let columns = ((PAGE_WIDTH + GAP) / (width + GAP)).floor().max(1.0) as usize;
+	let rows = ((PAGE_HEIGHT - 2.0 * GAP) / (height + GAP)).floor().max(1.0) as usize;
+	let per_page = columns * rows;
+
+	let margin_x = (PAGE_WIDTH - columns as f32 * width - (columns - 1) as f32 * GAP) / 2.0;
+	let margin_y = (PAGE_HEIGHT - rows as f32 * height - (rows - 1) as f32 * GAP) / 2.0;
+
+	let indices: Vec<usize> = (0..assignment.players.len()).collect();
+	indices
+		.chunks(per_page)
+		.map(|page| {
+			page.iter()
+				.enumerate()
+				.map(|(slot, &index)| Placement {
+					x: margin_x + (slot % columns) as f32 * (width + GAP),
+					y: margin_y + (slot / columns) as f32 * (height + GAP),
+					player: &assignment.players[index].0,
+					role: assignment.players[index].1.clone(),
+					teammates: assignment.describe_allies(index),
+				})
+				.collect()
+		})
+		.collect()
+}
+
+/**
+ * Формирует HTML-документ с карточками, разложенными по листам A4.
+ */
+pub fn render_html(assignment: &Assignment, options: &CardOptions) -> String {
+	let (width, height) = card_size(options);
+	let mut pages = String::new();
+
+	for page in layout(assignment, options) {
+		pages.push_str("<section class=\"page\">");
+		for card in page {
+			let role = card.role;
+			let color = role.get_faction().get_color();
+			let icon = if options.icons { escape_html(role.get_icon()) } else { String::new() };
+			let team = if card.teammates.is_empty() {
+				String::new()
+			} else {
+				format!("<p class=\"team\">Ваша команда: {}</p>", escape_html(&card.teammates))
+			};
+			let role_panel = format!(
+				"<div class=\"band\" style=\"background:{color}\"><span class=\"icon\">{icon}</span>{}</div>\
+				<div class=\"faction\" style=\"color:{color}\">{}</div><p>{}</p>{team}",
+				escape_html(role.get_name()),
+				escape_html(role.get_faction().get_name()),
+				escape_html(role.get_description())
+			);
+
+			let content = if options.folded {
+				format!(
+					"<div class=\"panel name-panel\"><small>Игрок</small><strong>{}</strong>\
+					<small>Не разворачивайте до начала игры</small></div>\
+					<div class=\"panel role-panel\">{role_panel}</div>\
+					<div class=\"panel cover\"><span>Загните эту часть на роль</span></div>",
+					escape_html(card.player)
+				)
+			} else {
+				format!("<div class=\"player\">Игрок: {}</div>{role_panel}", escape_html(card.player))
+			};
+
+			pages.push_str(&format!(
+				"<div class=\"card\" style=\"left:{:.1}mm;top:{:.1}mm;width:{width}mm;height:{height}mm;border-color:{color}\">{content}</div>",
+				card.x, card.y
+			));
+		}
+		pages.push_str("</section>");
+	}
+
+	// Лист ведущего печатается отдельной страницей после карточек.
+	let rows: String = (0..assignment.players.len())
+		.map(|index| {
+			let (player, role) = &assignment.players[index];
+			format!(
+				"<tr><td>{}</td><td>{}</td><td style=\"color:{}\">{}</td><td>{}</td></tr>",
+				index + 1,
+				escape_html(player),
+				role.get_faction().get_color(),
+				escape_html(role.get_name()),
+				escape_html(&assignment.describe_allies(index))
+			)
+		})
+		.collect();
+	pages.push_str(&format!(
+		"<section class=\"page host\"><h1>Лист ведущего</h1><table>\
+		<tr><th>Место</th><th>Игрок</th><th>Роль</th><th>Знает союзников</th></tr>{rows}</table></section>"
+	));
+
+	format!(
+		"<!DOCTYPE html><html lang=\"ru\"><head><meta charset=\"utf-8\"><title>Карточки ролей</title><style>\
+		@page{{size:A4;margin:0;}}body{{margin:0;font-family:sans-serif;}}\
+		.page{{position:relative;width:{PAGE_WIDTH}mm;height:{PAGE_HEIGHT}mm;overflow:hidden;page-break-after:always;}}\
+		.card{{position:absolute;box-sizing:border-box;border:0.6mm solid;border-radius:2mm;overflow:hidden;}}\
+		.player{{font-size:3mm;padding:1.5mm 3mm;color:#444;}}\
+		.band{{color:#fff;font-size:5mm;font-weight:bold;padding:2mm 3mm;}}.icon{{margin-right:2mm;}}\
+		.faction{{font-size:2.8mm;font-weight:bold;padding:1mm 3mm 0;}}\
+		p{{font-size:2.8mm;line-height:1.3;margin:1mm 3mm;}}.team{{font-weight:bold;}}\
+		.host{{box-sizing:border-box;padding:15mm;height:auto;min-height:{PAGE_HEIGHT}mm;overflow:visible;}}.host table{{border-collapse:collapse;width:100%;font-size:4mm;}}\
+		.host td,.host th{{border:0.3mm solid #999;padding:1.5mm 2mm;text-align:left;}}\
+		.panel{{box-sizing:border-box;height:{FOLDED_PANEL_HEIGHT}mm;border-bottom:0.3mm dashed #888;overflow:hidden;}}\
+		.name-panel{{display:flex;flex-direction:column;align-items:center;justify-content:center;gap:2mm;}}\
+		.name-panel strong{{font-size:7mm;}}.name-panel small{{font-size:2.6mm;color:#666;}}\
+		.cover{{border-bottom:none;display:flex;align-items:center;justify-content:center;\
+		background:repeating-linear-gradient(45deg,#333 0 1mm,#777 1mm 2mm);-webkit-print-color-adjust:exact;print-color-adjust:exact;}}\
+		.cover span{{background:#fff;font-size:2.6mm;padding:1mm 2mm;}}\
+		</style></head><body>{pages}</body></html>"
+	)
+}
+
+/**
+ * Формирует SVG-изображения листов A4 с карточками (по одному на лист).
+ */
+pub fn render_svg_pages(assignment: &Assignment, options: &CardOptions) -> Vec<String> {
+	let (width, height) = card_size(options);
+
+	let mut pages: Vec<String> = layout(assignment, options)
+		.into_iter()
+		.map(|page| {
+			let mut body = String::new();
+			for card in page {
+				let (x, y) = (card.x, card.y);
+				let color = card.role.get_faction().get_color();
+
+				if options.folded {
+					body.push_str(&format!(
+						"<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"2.6\" fill=\"#666\" text-anchor=\"middle\">Игрок</text>\
+						<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"7\" font-weight=\"bold\" text-anchor=\"middle\">{}</text>\
+						<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"2.6\" fill=\"#666\" text-anchor=\"middle\">Не разворачивайте до начала игры</text>",
+						x + width / 2.0, y + 14.0,
+						x + width / 2.0, y + 25.0, escape_html(card.player),
+						x + width / 2.0, y + 34.0
+					));
+					body.push_str(&svg_role_panel(card.role, &card.teammates, x, y + FOLDED_PANEL_HEIGHT, options.icons));
+
+					// Клапан с плотной штриховкой, чтобы роль не просвечивала через бумагу.
+					let cover_y = y + 2.0 * FOLDED_PANEL_HEIGHT;
+					body.push_str(&format!(
+						"<rect x=\"{x:.1}\" y=\"{cover_y:.1}\" width=\"{width}\" height=\"{FOLDED_PANEL_HEIGHT}\" fill=\"url(#cover)\"/>\
+						<rect x=\"{:.1}\" y=\"{:.1}\" width=\"44\" height=\"6\" fill=\"#fff\"/>\
+						<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"2.6\" text-anchor=\"middle\">Загните эту часть на роль</text>",
+						x + width / 2.0 - 22.0, cover_y + FOLDED_PANEL_HEIGHT / 2.0 - 4.0,
+						x + width / 2.0, cover_y + FOLDED_PANEL_HEIGHT / 2.0
+					));
+
+					// Линии сгиба.
+					for fold in 1..3 {
+						let fold_y = y + fold as f32 * FOLDED_PANEL_HEIGHT;
+						body.push_str(&format!(
+							"<line x1=\"{x:.1}\" y1=\"{fold_y:.1}\" x2=\"{:.1}\" y2=\"{fold_y:.1}\" stroke=\"#888\" stroke-width=\"0.3\" stroke-dasharray=\"2 1.5\"/>",
+							x + width
+						));
+					}
+				} else {
+					body.push_str(&format!(
+						"<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"3\" fill=\"#444\">Игрок: {}</text>",
+						x + 3.0, y + 5.0, escape_html(card.player)
+					));
+					body.push_str(&svg_role_panel(card.role, &card.teammates, x, y + 7.0, options.icons));
+				}
+
+				body.push_str(&format!(
+					"<rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{width}\" height=\"{height}\" rx=\"2\" fill=\"none\" stroke=\"{color}\" stroke-width=\"0.6\"/>"
+				));
+			}
+
+			svg_page(&body)
+		})
+		.collect();
+
+	// Лист ведущего: таблица мест, игроков, ролей и известных союзников.
+	// За большим столом таблица продолжается на следующих листах.
+	let seats: Vec<usize> = (0..assignment.players.len()).collect();
+	for (sheet, chunk) in seats.chunks(HOST_ROWS_PER_PAGE).enumerate() {
+		let title = if sheet == 0 { "Лист ведущего".to_string() } else { format!("Лист ведущего (лист {})", sheet + 1) };
+		let mut body = format!("<text x=\"15\" y=\"22\" font-size=\"8\" font-weight=\"bold\">{title}</text>");
+		for (row, &index) in chunk.iter().enumerate() {
+			let (player, role) = &assignment.players[index];
+			let y = 34.0 + row as f32 * 7.0;
+			let teammates = assignment.describe_allies(index);
+			body.push_str(&format!(
+				"<text x=\"15\" y=\"{y:.1}\" font-size=\"4\">{}.</text>\
+				<text x=\"25\" y=\"{y:.1}\" font-size=\"4\">{}</text>\
+				<text x=\"85\" y=\"{y:.1}\" font-size=\"4\" fill=\"{}\">{}</text>\
+				<text x=\"135\" y=\"{y:.1}\" font-size=\"3.2\">{}</text>",
+				index + 1,
+				escape_html(player),
+				role.get_faction().get_color(),
+				escape_html(role.get_name()),
+				escape_html(&teammates)
+			));
+		}
+		pages.push(svg_page(&body));
+	}
+
+	pages
+}
+
+/**
+ * Оборачивает содержимое листа A4 в SVG-документ.
+ */
+fn svg_page(body: &str) -> String {
+	format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{PAGE_WIDTH}mm\" height=\"{PAGE_HEIGHT}mm\" viewBox=\"0 0 {PAGE_WIDTH} {PAGE_HEIGHT}\" font-family=\"sans-serif\">\
+		<defs><pattern id=\"cover\" width=\"2\" height=\"2\" patternUnits=\"userSpaceOnUse\" patternTransform=\"rotate(45)\">\
+		<rect width=\"2\" height=\"2\" fill=\"#777\"/><rect width=\"1\" height=\"2\" fill=\"#333\"/></pattern></defs>\
+		<rect width=\"{PAGE_WIDTH}\" height=\"{PAGE_HEIGHT}\" fill=\"#fff\"/>{body}</svg>"
+	)
+}
+
+/**
+ * Рисует блок роли: цветная полоса с названием, сторона, описание и известные союзники.
+ */
+fn svg_role_panel(role: Role, teammates: &str, x: f32, y: f32, icons: bool) -> String {
+	let color = role.get_faction().get_color();
+	let title = if icons {
+		format!("{} {}", role.get_icon(), role.get_name())
+	} else {
+		role.get_name().to_string()
+	};
+
+	let mut panel = format!(
+		"<rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{CARD_WIDTH}\" height=\"9\" fill=\"{color}\"/>\
+		<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"5\" font-weight=\"bold\" fill=\"#fff\">{}</text>\
+		<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"2.8\" font-weight=\"bold\" fill=\"{color}\">{}</text>",
+		x + 3.0, y + 6.5, escape_html(&title),
+		x + 3.0, y + 13.0, escape_html(role.get_faction().get_name())
+	);
+
+	// SVG не переносит строки сам, поэтому описание разбивается по словам вручную.
+	let lines = wrap_text(role.get_description(), 52);
+	for (line_index, line) in lines.iter().enumerate() {
+		panel.push_str(&format!(
+			"<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"2.6\">{}</text>",
+			x + 3.0,
+			y + 17.5 + line_index as f32 * 3.4,
+			escape_html(line)
+		));
+	}
+
+	if !teammates.is_empty() {
+		panel.push_str(&format!(
+			"<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"2.6\" font-weight=\"bold\">Ваша команда: {}</text>",
+			x + 3.0,
+			y + 18.5 + lines.len() as f32 * 3.4,
+			escape_html(teammates)
+		));
+	}
+
+	panel
+}
+
+/**
+ * Разбивает текст на строки не длиннее `max_chars` символов по границам слов.
+ */
+fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+	let mut lines = Vec::new();
+	let mut current = String::new();
+
+	for word in text.split_whitespace() {
+		let needed = current.chars().count() + word.chars().count() + 1;
+		if !current.is_empty() && needed > max_chars {
+			lines.push(std::mem::take(&mut current));
+		}
+		if !current.is_empty() {
+			current.push(' ');
+		}
+		current.push_str(word);
+	}
+	if !current.is_empty() {
+		lines.push(current);
+	}
+
+	lines
+}
+
+/**
+ * Экранирует специальные символы HTML и XML.
+ */
+pub fn escape_html(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&#39;"),
+			_ => escaped.push(c),
+		}
+	}
+	escaped
+}
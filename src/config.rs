@@ -0,0 +1,497 @@
+//! Файл настроек `config.toml` в каталоге настроек пользователя (см. `--paths`).
+//!
+//! Значения применяются в порядке убывания приоритета: аргументы командной строки,
+//! переменные окружения, файл настроек, встроенные значения.
+//!
+//! ```toml
+//! [generation]
+//! game_mode = "extended"      # режим интерактивной генерации: classic или extended
+//! min_players = 4             # допустимый диапазон игроков (по умолчанию 6-20, в пределах 4-60)
+//! max_players = 36
+//! maniac_min_players = 10     # с какого количества игроков появляется Маньяк
+//!
+//! [output]
+//! format = "cards"            # text, encrypted или cards (для --headless)
+//! cards = "svg"               # html или svg
+//! folded = true
+//! icons = false
+//! directory = "/home/host/mafia"   # вместо каталога данных
+//! template = "/home/host/role.txt" # шаблон файла роли
+//!
+//! [locale]
+//! language = "ru"
+//! date_format = "yyyy-mm-dd"  # или dd.mm.yyyy
+//!
+//! [update]
+//! source = "https://mirror.example.com/mafia"
+//! channel = "beta"
+//! auto_check = false
+//!
+//! [[roles]]                   # пользовательская роль вместо мирного жителя или мафиози
+//! name = "Любовница"
+//! description = "Ночью выбирает игрока, который на следующий день не может голосовать."
+//! faction = "town"            # town, mafia или neutral
+//! count = 1
+//! min_players = 10
+//! icon = "💋"
+//! ```
+
+use mafia_game_generator::cards::{CardFormat, CardOptions};
+use mafia_game_generator::error::AppError;
+use mafia_game_generator::game_setup::{GameConfig, GameMode, OutputFormat, DEFAULT_MAX_PLAYERS, DEFAULT_MIN_PLAYERS, MIN_PLAYERS};
+use mafia_game_generator::role::{CustomRole, Faction};
+use mafia_game_generator::template::{DateFormat, RoleTemplate};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::PathBuf;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+// Переменные окружения для настроек генерации и вывода.
+const GAME_MODE_ENV: &str = "MAFIA_GAME_MODE";
+const OUTPUT_FORMAT_ENV: &str = "MAFIA_OUTPUT_FORMAT";
+const OUTPUT_DIR_ENV: &str = "MAFIA_OUTPUT_DIR";
+// Единственный поддерживаемый язык интерфейса.
+const DEFAULT_LANGUAGE: &str = "ru";
+
+/// Содержимое файла настроек. Отсутствующие значения берутся из окружения или встроенных.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+	pub generation: GenerationSection,
+	pub output: OutputSection,
+	pub locale: LocaleSection,
+	pub update: UpdateSection,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub roles: Vec<RoleSection>,
+}
+
+/// Раздел `[generation]`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenerationSection {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub game_mode: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub min_players: Option<u8>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub max_players: Option<u8>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub maniac_min_players: Option<u8>,
+}
+
+/// Раздел `[output]`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputSection {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub format: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cards: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub folded: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub icons: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub directory: Option<PathBuf>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub template: Option<PathBuf>,
+}
+
+/// Раздел `[locale]`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LocaleSection {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub language: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub date_format: Option<String>,
+}
+
+/// Раздел `[update]`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateSection {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub source: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub channel: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub auto_check: Option<bool>,
+}
+
+/// Пользовательская роль `[[roles]]`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleSection {
+	pub name: String,
+	pub description: String,
+	pub faction: String,
+	#[serde(default = "default_role_count")]
+	pub count: u8,
+	#[serde(default)]
+	pub min_players: u8,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub icon: Option<String>,
+}
+
+fn default_role_count() -> u8 {
+	1
+}
+
+/**
+ * Путь к файлу настроек.
+ */
+pub fn config_path() -> PathBuf {
+	crate::paths::dirs().config.join(CONFIG_FILE_NAME)
+}
+
+/**
+ * Загружает и проверяет файл настроек, включая пользовательские роли;
+ * в игру они попадают через `Config::game_config`.
+ * Если файла нет, используются встроенные значения.
+ */
+pub fn load() -> Result<Config, AppError> {
+	let table = read_table()?;
+	let config = Config::from_table(table)?;
+	config.validate()?;
+	Ok(config)
+}
+
+/**
+ * Читает переменную окружения; пустое значение считается отсутствующим.
+ */
+pub fn env_value(name: &str) -> Result<Option<String>, AppError> {
+	match env::var(name) {
+		Ok(value) if !value.trim().is_empty() => Ok(Some(value)),
+		Ok(_) | Err(env::VarError::NotPresent) => Ok(None),
+		Err(e) => Err(e.into()),
+	}
+}
+
+impl Config {
+	fn from_table(table: toml::Table) -> Result<Self, AppError> {
+		Config::deserialize(table).map_err(|e| AppError::Config(format!("{}: {}", config_path().display(), e)))
+	}
+
+	// Проверяет значения файла, чтобы ошибка в нём обнаруживалась сразу, а не при генерации.
+	fn validate(&self) -> Result<(), AppError> {
+		if let Some(mode) = &self.generation.game_mode {
+			parse_game_mode(mode)?;
+		}
+		self.game_config(MIN_PLAYERS, GameMode::Classic)?;
+
+		if let Some(format) = &self.output.format {
+			parse_output_format(format)?;
+		}
+		if let Some(cards) = &self.output.cards {
+			parse_card_format(cards)?;
+		}
+
+		if let Some(language) = &self.locale.language
+			&& language.trim() != DEFAULT_LANGUAGE
+		{
+			return Err(AppError::Config(format!(
+				"язык '{}' не поддерживается, доступен только '{DEFAULT_LANGUAGE}'",
+				language.trim()
+			)));
+		}
+		if let Some(date_format) = &self.locale.date_format {
+			parse_date_format(date_format)?;
+		}
+
+		#[cfg(feature = "self-update")]
+		{
+			if let Some(source) = &self.update.source {
+				crate::updater::UpdateSource::parse(source)?;
+			}
+			if let Some(channel) = &self.update.channel {
+				crate::updater::UpdateChannel::parse(channel)?;
+			}
+		}
+
+		self.custom_roles()?;
+		Ok(())
+	}
+
+	fn custom_roles(&self) -> Result<Vec<CustomRole>, AppError> {
+		self.roles
+			.iter()
+			.map(|role| {
+				if role.name.trim().is_empty() {
+					return Err(AppError::Config("у пользовательской роли не указано имя".to_string()));
+				}
+				if role.count == 0 {
+					return Err(AppError::Config(format!("количество ролей '{}' должно быть больше нуля", role.name)));
+				}
+				let faction = match role.faction.trim().to_lowercase().as_str() {
+					"town" => Faction::Town,
+					"mafia" => Faction::Mafia,
+					"neutral" => Faction::Neutral,
+					other => {
+						return Err(AppError::Config(format!(
+							"неизвестная сторона '{other}' у роли '{}'. Допустимые значения: town, mafia, neutral",
+							role.name
+						)));
+					}
+				};
+				// Значок попадает в карточки и терминал как есть: разметка и управляющие символы в нём недопустимы.
+				let unsafe_icon = role.icon.as_deref().is_some_and(|icon| {
+					icon.chars().any(|c| c.is_control() || matches!(c, '<' | '>' | '&' | '"' | '\''))
+				});
+				if unsafe_icon {
+					return Err(AppError::Config(format!(
+						"значок роли '{}' не может содержать разметку или управляющие символы",
+						role.name
+					)));
+				}
+				Ok(CustomRole {
+					name: role.name.trim().to_string(),
+					description: role.description.trim().to_string(),
+					faction,
+					icon: role.icon.clone().unwrap_or_else(|| "🎭".to_string()),
+					count: role.count,
+					min_players: role.min_players,
+				})
+			})
+			.collect()
+	}
+
+	/// Режим игры для интерактивной генерации.
+	pub fn game_mode(&self) -> Result<GameMode, AppError> {
+		match env_value(GAME_MODE_ENV)?.or_else(|| self.generation.game_mode.clone()) {
+			Some(mode) => parse_game_mode(&mode),
+			None => Ok(GameMode::Classic),
+		}
+	}
+
+	/// Конфигурация игры с ограничениями из раздела `[generation]`.
+	pub fn game_config(&self, player_count: u8, game_mode: GameMode) -> Result<GameConfig, AppError> {
+		let generation = &self.generation;
+		let mut config = GameConfig::new(player_count, game_mode)
+			.with_player_limits(
+				generation.min_players.unwrap_or(DEFAULT_MIN_PLAYERS),
+				generation.max_players.unwrap_or(DEFAULT_MAX_PLAYERS),
+			)?
+			.with_custom_roles(self.custom_roles()?)?;
+		if let Some(maniac_min_players) = generation.maniac_min_players {
+			config = config.with_maniac_min_players(maniac_min_players);
+		}
+		Ok(config)
+	}
+
+	/// Формат вывода для `--headless`, если он не задан в командной строке.
+	pub fn output_format(&self) -> Result<OutputFormat, AppError> {
+		let format = env_value(OUTPUT_FORMAT_ENV)?.or_else(|| self.output.format.clone());
+		match format.as_deref().map(parse_output_format).transpose()? {
+			Some(OutputKind::Encrypted) => Ok(OutputFormat::Encrypted),
+			Some(OutputKind::Cards) => Ok(OutputFormat::Cards(self.card_options(None)?)),
+			Some(OutputKind::Text) | None => Ok(OutputFormat::Text),
+		}
+	}
+
+	/// Параметры карточек: формат из командной строки важнее формата из файла.
+	pub fn card_options(&self, cli_format: Option<CardFormat>) -> Result<CardOptions, AppError> {
+		let format = match cli_format {
+			Some(format) => format,
+			None => self.output.cards.as_deref().map(parse_card_format).transpose()?.unwrap_or(CardFormat::Html),
+		};
+		Ok(CardOptions {
+			format,
+			folded: self.output.folded.unwrap_or(false),
+			icons: self.output.icons.unwrap_or(true),
+		})
+	}
+
+	/// Каталог для результатов определенного вида (`roles`, `cards`, `qr`).
+	pub fn output_dir(&self, name: &str) -> Result<PathBuf, AppError> {
+		match env_value(OUTPUT_DIR_ENV)?.map(PathBuf::from).or_else(|| self.output.directory.clone()) {
+			Some(directory) => Ok(directory.join(name)),
+			None => Ok(crate::paths::output_dir(name)),
+		}
+	}
+
+	/// Шаблон файла роли: из командной строки, из файла настроек или встроенный.
+	pub fn role_template(&self, cli_path: Option<&str>) -> Result<RoleTemplate, AppError> {
+		let template = match cli_path.map(PathBuf::from).or_else(|| self.output.template.clone()) {
+			Some(path) => RoleTemplate::from_file(&path.to_string_lossy()).map_err(|e| match e {
+				AppError::Io(err) => AppError::Config(format!("не удалось прочитать шаблон '{}': {}", path.display(), err)),
+				other => other,
+			})?,
+			None => RoleTemplate::default(),
+		};
+		let date_format = self.locale.date_format.as_deref().map(parse_date_format).transpose()?.unwrap_or_default();
+		Ok(template.with_date_format(date_format))
+	}
+
+	/// Действующие настройки с учетом переменных окружения и встроенных значений (для `--config show`).
+	pub fn effective(&self) -> Result<Config, AppError> {
+		let game_mode = match self.game_mode()? {
+			GameMode::Classic => "classic",
+			GameMode::Extended => "extended",
+		};
+		let limits = self.game_config(MIN_PLAYERS, GameMode::Classic)?;
+		let (format, cards) = match self.output_format()? {
+			OutputFormat::Text => ("text", self.card_options(None)?),
+			OutputFormat::Encrypted => ("encrypted", self.card_options(None)?),
+			OutputFormat::Cards(options) => ("cards", options),
+		};
+		let directory = self.output_dir("")?;
+		let date_format = match self.locale.date_format.as_deref().map(parse_date_format).transpose()?.unwrap_or_default() {
+			DateFormat::DayMonthYear => "dd.mm.yyyy",
+			DateFormat::Iso => "yyyy-mm-dd",
+		};
+
+		#[cfg(feature = "self-update")]
+		let update = {
+			let settings = crate::updater::UpdateSettings::resolve(None, None, self)?;
+			UpdateSection {
+				source: Some(settings.source.to_string()),
+				channel: Some(settings.channel.to_string()),
+				auto_check: Some(settings.auto_check),
+			}
+		};
+		#[cfg(not(feature = "self-update"))]
+		let update = UpdateSection::default();
+
+		Ok(Config {
+			generation: GenerationSection {
+				game_mode: Some(game_mode.to_string()),
+				min_players: Some(limits.min_players),
+				max_players: Some(limits.max_players),
+				maniac_min_players: Some(limits.maniac_min_players),
+			},
+			output: OutputSection {
+				format: Some(format.to_string()),
+				cards: Some(match cards.format {
+					CardFormat::Html => "html".to_string(),
+					CardFormat::Svg => "svg".to_string(),
+				}),
+				folded: Some(cards.folded),
+				icons: Some(cards.icons),
+				directory: Some(directory.components().collect()),
+				template: self.output.template.clone(),
+			},
+			locale: LocaleSection {
+				language: Some(DEFAULT_LANGUAGE.to_string()),
+				date_format: Some(date_format.to_string()),
+			},
+			update,
+			roles: self
+				.roles
+				.iter()
+				.map(|role| RoleSection {
+					name: role.name.clone(),
+					description: role.description.clone(),
+					faction: role.faction.clone(),
+					count: role.count,
+					min_players: role.min_players,
+					icon: role.icon.clone(),
+				})
+				.collect(),
+		})
+	}
+}
+
+/**
+ * Выводит действующие настройки в формате TOML.
+ */
+pub fn show() -> Result<(), AppError> {
+	let effective = load()?.effective()?;
+	let text = toml::to_string_pretty(&effective).map_err(|e| AppError::Config(e.to_string()))?;
+	println!("# Действующие настройки. Файл: {}", config_path().display());
+	print!("{text}");
+	Ok(())
+}
+
+/**
+ * Записывает значение `раздел.параметр` в файл настроек.
+ * Значение разбирается как TOML (числа, true/false), иначе сохраняется как строка.
+ */
+pub fn set(key: &str, value: &str) -> Result<(), AppError> {
+	let Some((section, field)) = key.split_once('.') else {
+		return Err(AppError::Config(format!(
+			"параметр '{key}' нужно указывать вместе с разделом, например generation.game_mode"
+		)));
+	};
+	if section == "roles" {
+		return Err(AppError::Config("пользовательские роли редактируются в самом файле настроек".to_string()));
+	}
+
+	let mut table = read_table()?;
+	let entry = table
+		.entry(section.to_string())
+		.or_insert_with(|| toml::Value::Table(toml::Table::new()));
+	let toml::Value::Table(section_table) = entry else {
+		return Err(AppError::Config(format!("'{section}' в файле настроек не является разделом")));
+	};
+	section_table.insert(field.to_string(), parse_value(value));
+
+	// Новое значение проверяется так же, как при загрузке, до записи на диск.
+	let text = toml::to_string_pretty(&table).map_err(|e| AppError::Config(e.to_string()))?;
+	let config = Config::from_table(table)?;
+	config.validate()?;
+
+	let path = config_path();
+	crate::paths::ensure(&crate::paths::dirs().config)?;
+	std::fs::write(&path, text)?;
+	println!("Сохранено: {key} = {value} ({})", path.display());
+	Ok(())
+}
+
+// Читает файл настроек как таблицу TOML. Отсутствующий файл — пустая таблица.
+fn read_table() -> Result<toml::Table, AppError> {
+	let path = config_path();
+	let text = match std::fs::read_to_string(&path) {
+		Ok(text) => text,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(toml::Table::new()),
+		Err(e) => return Err(e.into()),
+	};
+	text.parse::<toml::Table>().map_err(|e| AppError::Config(format!("{}: {}", path.display(), e)))
+}
+
+// Разбирает значение из командной строки как значение TOML, иначе считает его строкой.
+fn parse_value(value: &str) -> toml::Value {
+	format!("value = {value}")
+		.parse::<toml::Table>()
+		.ok()
+		.and_then(|mut table| table.remove("value"))
+		.unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+/// Вид вывода из раздела `[output]`.
+enum OutputKind {
+	Text,
+	Encrypted,
+	Cards,
+}
+
+fn parse_output_format(value: &str) -> Result<OutputKind, AppError> {
+	match value.trim().to_lowercase().as_str() {
+		"text" => Ok(OutputKind::Text),
+		"encrypted" => Ok(OutputKind::Encrypted),
+		"cards" => Ok(OutputKind::Cards),
+		other => Err(AppError::Config(format!(
+			"неизвестный формат вывода '{other}'. Допустимые значения: text, encrypted, cards"
+		))),
+	}
+}
+
+fn parse_card_format(value: &str) -> Result<CardFormat, AppError> {
+	match value.trim().to_lowercase().as_str() {
+		"html" => Ok(CardFormat::Html),
+		"svg" => Ok(CardFormat::Svg),
+		other => Err(AppError::Config(format!("неизвестный формат карточек '{other}'. Допустимые значения: html, svg"))),
+	}
+}
+
+fn parse_game_mode(value: &str) -> Result<GameMode, AppError> {
+	GameMode::parse(value).ok_or_else(|| {
+		AppError::Config(format!("неизвестный режим игры '{}'. Допустимые значения: classic, extended", value.trim()))
+	})
+}
+
+fn parse_date_format(value: &str) -> Result<DateFormat, AppError> {
+	DateFormat::parse(value).ok_or_else(|| {
+		AppError::Config(format!("неизвестный формат даты '{}'. Допустимые значения: dd.mm.yyyy, yyyy-mm-dd", value.trim()))
+	})
+}
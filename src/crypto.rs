@@ -0,0 +1,133 @@
+use crate::error::AppError;
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::{Rng, RngCore};
+
+/// Сигнатура зашифрованного файла роли (и версия формата).
+/// Текст дополняется до `PADDED_BLOCK`, чтобы размер файла не выдавал роль.
+const MAGIC: &[u8; 4] = b"MGG2";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+// Размер блока, до которого дополняется текст; в начале хранится длина текста (4 байта).
+const PADDED_BLOCK: usize = 4096;
+const LENGTH_PREFIX_LEN: usize = 4;
+
+/// Допустимая длина PIN-кода.
+const MIN_PIN_LEN: usize = 4;
+const MAX_PIN_LEN: usize = 12;
+
+/**
+ * Генерирует случайный PIN-код из шести цифр.
+ */
+pub fn generate_pin() -> String {
+	format!("{:06}", rand::rng().random_range(0..1_000_000))
+}
+
+/**
+ * Проверяет, что PIN-код состоит только из цифр и имеет допустимую длину.
+ */
+pub fn validate_pin(pin: &str) -> Result<(), AppError> {
+	if !(MIN_PIN_LEN..=MAX_PIN_LEN).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+		return Err(AppError::InvalidPin { min: MIN_PIN_LEN, max: MAX_PIN_LEN });
+	}
+	Ok(())
+}
+
+/**
+ * Шифрует данные ключом, полученным из PIN-кода.
+ * Формат результата: сигнатура, соль, nonce и шифротекст ChaCha20-Poly1305.
+ * Перед шифрованием текст дополняется нулями до кратного `PADDED_BLOCK` размера,
+ * поэтому все файлы ролей одного размера.
+ */
+pub fn encrypt_with_pin(plaintext: &[u8], pin: &str) -> Result<Vec<u8>, AppError> {
+	let padded = pad(plaintext)?;
+
+	let mut rng = rand::rng();
+	let mut salt = [0u8; SALT_LEN];
+	let mut nonce = [0u8; NONCE_LEN];
+	rng.fill_bytes(&mut salt);
+	rng.fill_bytes(&mut nonce);
+
+	let cipher = ChaCha20Poly1305::new(&derive_key(pin, &salt)?);
+	let ciphertext = cipher
+		.encrypt(Nonce::from_slice(&nonce), padded.as_slice())
+		.map_err(|_| AppError::Encryption("не удалось зашифровать данные".to_string()))?;
+
+	let mut data = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+	data.extend_from_slice(MAGIC);
+	data.extend_from_slice(&salt);
+	data.extend_from_slice(&nonce);
+	data.extend_from_slice(&ciphertext);
+	Ok(data)
+}
+
+/**
+ * Расшифровывает данные, созданные `encrypt_with_pin`.
+ */
+pub fn decrypt_with_pin(data: &[u8], pin: &str) -> Result<Vec<u8>, AppError> {
+	let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+	if data.len() <= header_len {
+		return Err(corrupted());
+	}
+	if &data[..MAGIC.len()] != MAGIC {
+		return Err(corrupted());
+	}
+
+	let salt = &data[MAGIC.len()..MAGIC.len() + SALT_LEN];
+	let nonce = &data[MAGIC.len() + SALT_LEN..header_len];
+
+	let cipher = ChaCha20Poly1305::new(&derive_key(pin, salt)?);
+	let plaintext = cipher
+		.decrypt(Nonce::from_slice(nonce), &data[header_len..])
+		.map_err(|_| AppError::WrongPin)?;
+
+	unpad(plaintext)
+}
+
+/**
+ * Дополняет текст до кратного `PADDED_BLOCK` размера: длина текста, сам текст и нули.
+ */
+fn pad(plaintext: &[u8]) -> Result<Vec<u8>, AppError> {
+	let length = u32::try_from(plaintext.len()).map_err(|_| AppError::Encryption("слишком большой файл роли".to_string()))?;
+	let padded_len = (LENGTH_PREFIX_LEN + plaintext.len()).div_ceil(PADDED_BLOCK) * PADDED_BLOCK;
+
+	let mut padded = Vec::with_capacity(padded_len);
+	padded.extend_from_slice(&length.to_le_bytes());
+	padded.extend_from_slice(plaintext);
+	padded.resize(padded_len, 0);
+	Ok(padded)
+}
+
+/**
+ * Убирает дополнение, добавленное `pad`.
+ */
+fn unpad(mut padded: Vec<u8>) -> Result<Vec<u8>, AppError> {
+	let prefix: [u8; LENGTH_PREFIX_LEN] = padded
+		.get(..LENGTH_PREFIX_LEN)
+		.and_then(|prefix| prefix.try_into().ok())
+		.ok_or_else(corrupted)?;
+	let length = u32::from_le_bytes(prefix) as usize;
+	if LENGTH_PREFIX_LEN + length > padded.len() {
+		return Err(corrupted());
+	}
+	padded.truncate(LENGTH_PREFIX_LEN + length);
+	padded.drain(..LENGTH_PREFIX_LEN);
+	Ok(padded)
+}
+
+fn corrupted() -> AppError {
+	AppError::Encryption("файл повреждён или не является зашифрованным файлом роли".to_string())
+}
+
+/**
+ * Получает ключ шифрования из PIN-кода с помощью Argon2id.
+ * Медленная функция усложняет перебор коротких PIN-кодов.
+ */
+fn derive_key(pin: &str, salt: &[u8]) -> Result<Key, AppError> {
+	let mut key = Key::default();
+	Argon2::default()
+		.hash_password_into(pin.as_bytes(), salt, &mut key)
+		.map_err(|e| AppError::Encryption(e.to_string()))?;
+	Ok(key)
+}
@@ -2,7 +2,11 @@ use std::fmt;
 use std::num::ParseIntError;
 use std::env::VarError;
 
+/// Ошибки библиотеки и программы.
+/// Новые варианты могут добавляться без нарушения совместимости, поэтому `match` вне библиотеки
+/// должен содержать ветку `_`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum AppError {
 	/// Ошибка ввода/вывода. Оборачивает стандартную ошибку `std::io::Error`.
 	Io(std::io::Error),
@@ -14,6 +18,9 @@ pub enum AppError {
 	/// значение было введено, и какие являются допустимыми.
 	InvalidPlayerCount { given: u8, min: u8, max: u8 },
 
+	/// Для заданного количества игроков ключевых ролей получилось больше, чем мест за столом.
+	InvalidRoleLayout { players: u8 },
+
 	/// Введено пустое имя игрока.
 	EmptyPlayerName,
 
@@ -28,6 +35,34 @@ pub enum AppError {
 
 	/// Недопустимое имя файла (возможная попытка path traversal).
 	InvalidFileName(String),
+
+	/// Некорректный PIN-код для шифрования файла роли.
+	InvalidPin { min: usize, max: usize },
+
+	/// Неверный PIN-код: файл роли не удалось расшифровать.
+	WrongPin,
+
+	/// Ошибка шифрования или повреждённый зашифрованный файл.
+	Encryption(String),
+
+	/// Ошибка в пользовательском шаблоне файла роли.
+	InvalidTemplate(String),
+
+	/// Ошибка построения QR-кода (например, слишком длинная ссылка).
+	QrCode(String),
+
+	/// Неизвестный числовой код роли.
+	InvalidRole(u8),
+
+	/// Загруженное обновление не прошло проверку контрольной суммы или подписи.
+	UpdateVerification(String),
+
+	/// Недопустимое значение в файле настроек или в переменной окружения.
+	Config(String),
+
+	/// В каталоге вывода оказалось не столько файлов ролей, сколько игроков
+	/// (например, имена двух игроков дали одно и то же имя файла).
+	OutputFileCount { expected: usize, found: usize },
 }
 
 impl fmt::Display for AppError {
@@ -39,6 +74,10 @@ impl fmt::Display for AppError {
 				f,
 				"Ошибка конфигурации: для игры требуется от {min} до {max} игроков. Вы введи: {given}."
 			),
+			AppError::InvalidRoleLayout { players } => write!(
+				f,
+				"Ошибка конфигурации: для {players} игроков ключевых ролей получается больше, чем мест за столом."
+			),
 			AppError::EmptyPlayerName => write!(f, "Ошибка ввода: имя игрока не может быть пустым."),
 			AppError::InvalidCharactersInName(name) => write!(
 				f,
@@ -54,6 +93,21 @@ impl fmt::Display for AppError {
 			AppError::InvalidFileName(name) => {
 				write!(f, "Ошибка безопасности: имя файла '{name}' недопустимо.")
 			}
+			AppError::InvalidPin { min, max } => write!(
+				f,
+				"Ошибка ввода: PIN-код должен состоять из цифр, длина от {min} до {max} символов."
+			),
+			AppError::WrongPin => write!(f, "Ошибка: неверный PIN-код."),
+			AppError::Encryption(msg) => write!(f, "Ошибка шифрования: {msg}"),
+			AppError::InvalidTemplate(msg) => write!(f, "Ошибка в шаблоне файла роли: {msg}"),
+			AppError::QrCode(err) => write!(f, "Ошибка создания QR-кода: {err}"),
+			AppError::InvalidRole(code) => write!(f, "Ошибка: неизвестный код роли {code}."),
+			AppError::UpdateVerification(msg) => write!(f, "Обновление отклонено: {msg}"),
+			AppError::Config(msg) => write!(f, "Ошибка в настройках: {msg}"),
+			AppError::OutputFileCount { expected, found } => write!(
+				f,
+				"Ошибка проверки: ожидалось {expected} файлов ролей, но в папке их {found}."
+			),
 		}
 	}
 }
@@ -0,0 +1,252 @@
+//! C ABI для вызова генератора из других языков (например, из GDExtension в Godot).
+//!
+//! Все функции возвращают `MggStatus`. Память, выделенная библиотекой,
+//! освобождается только соответствующими функциями `mgg_*_free`.
+//! Заголовочный файл `include/mafia_game_generator.h` генерируется cbindgen
+//! (`cbindgen --config cbindgen.toml --output include/mafia_game_generator.h`).
+
+use crate::error::AppError;
+use crate::game_setup::{get_roles_for_players, shuffle_roles_with_seed, GameConfig, GameMode, MAX_PLAYERS, MIN_PLAYERS};
+use crate::role::Role;
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+/// Код результата вызова.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MggStatus {
+	/// Успешно.
+	Ok = 0,
+	/// Передан нулевой указатель или недопустимое значение аргумента.
+	InvalidArgument = 1,
+	/// Количество игроков вне допустимого диапазона.
+	InvalidPlayerCount = 2,
+	/// Неизвестный код роли.
+	InvalidRole = 3,
+	/// Ошибка ввода/вывода.
+	Io = 4,
+	/// Неверный PIN-код.
+	WrongPin = 5,
+	/// Ошибка шифрования.
+	Encryption = 6,
+	/// Прочие ошибки.
+	Internal = 255,
+}
+
+thread_local! {
+	/// Текст последней ошибки в текущем потоке.
+	static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/**
+ * Преобразует ошибку приложения в код результата и запоминает её текст.
+ */
+fn fail(err: AppError) -> MggStatus {
+	let status = match &err {
+		AppError::InvalidPlayerCount { .. } | AppError::InvalidRoleLayout { .. } => MggStatus::InvalidPlayerCount,
+		AppError::InvalidRole(_) => MggStatus::InvalidRole,
+		AppError::Io(_) | AppError::OutputFileCount { .. } => MggStatus::Io,
+		AppError::WrongPin => MggStatus::WrongPin,
+		AppError::Encryption(_) => MggStatus::Encryption,
+		AppError::ParseInt(_)
+		| AppError::EmptyPlayerName
+		| AppError::InvalidCharactersInName(_)
+		| AppError::DuplicatePlayerName(_)
+		| AppError::InvalidFileName(_)
+		| AppError::InvalidPin { .. }
+		| AppError::InvalidTemplate(_)
+		| AppError::Config(_) => MggStatus::InvalidArgument,
+		AppError::UpdateConfig(_) | AppError::UpdateVerification(_) | AppError::QrCode(_) => MggStatus::Internal,
+	};
+	LAST_ERROR.with(|last| *last.borrow_mut() = Some(err.to_string()));
+	status
+}
+
+/**
+ * Сообщает о недопустимом аргументе.
+ */
+fn invalid_argument(message: &str) -> MggStatus {
+	LAST_ERROR.with(|last| *last.borrow_mut() = Some(message.to_string()));
+	MggStatus::InvalidArgument
+}
+
+/**
+ * Передаёт строку вызывающей стороне; освобождается через `mgg_string_free`.
+ */
+fn into_c_string(text: &str) -> *mut c_char {
+	// Строки ролей не содержат нулевых байтов, поэтому ошибки здесь быть не может.
+	CString::new(text).map(CString::into_raw).unwrap_or(ptr::null_mut())
+}
+
+/// Генерирует набор ролей (без перемешивания) для заданного количества игроков
+/// (от `MIN_PLAYERS` до `MAX_PLAYERS`, то есть от 4 до 60).
+/// `game_mode`: 0 — классический, 1 — расширенный.
+/// Коды ролей: 0 — Мирный житель, 1 — Мафия, 2 — Дон, 3 — Шериф, 4 — Доктор, 5 — Маньяк.
+/// Результат освобождается через `mgg_roles_free`.
+///
+/// # Safety
+///
+/// `out_roles` и `out_len` должны быть действительными указателями для записи.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mgg_get_roles_for_players(
+	player_count: u8,
+	game_mode: u8,
+	out_roles: *mut *mut u8,
+	out_len: *mut usize,
+) -> MggStatus {
+	if out_roles.is_null() || out_len.is_null() {
+		return invalid_argument("out_roles и out_len не могут быть NULL");
+	}
+	let game_mode = match game_mode {
+		0 => GameMode::Classic,
+		1 => GameMode::Extended,
+		_ => return invalid_argument("неизвестный режим игры"),
+	};
+
+	let roles = match GameConfig::new(player_count, game_mode)
+		.with_player_limits(MIN_PLAYERS, MAX_PLAYERS)
+		.and_then(|config| get_roles_for_players(&config))
+	{
+		Ok(roles) => roles,
+		Err(e) => return fail(e),
+	};
+
+	let Some(codes) = roles.iter().map(Role::code).collect::<Option<Box<[u8]>>>() else {
+		return invalid_argument("у пользовательских ролей нет числового кода");
+	};
+	let len = codes.len();
+	// SAFETY: указатели проверены выше, вызывающая сторона гарантирует их корректность.
+	unsafe {
+		*out_len = len;
+		*out_roles = Box::into_raw(codes).cast::<u8>();
+	}
+	MggStatus::Ok
+}
+
+/// Освобождает массив ролей, полученный из `mgg_get_roles_for_players`.
+///
+/// # Safety
+///
+/// `roles` и `len` должны быть ровно теми, что вернула библиотека (или `roles` равен NULL).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mgg_roles_free(roles: *mut u8, len: usize) {
+	if roles.is_null() {
+		return;
+	}
+	// SAFETY: массив был выделен как Box<[u8]> длиной `len`.
+	drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(roles, len)) });
+}
+
+/// Перемешивает массив ролей детерминированно по зерну `seed`.
+///
+/// # Safety
+///
+/// `roles` должен указывать на `len` доступных для записи байтов.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mgg_shuffle_roles(roles: *mut u8, len: usize, seed: u64) -> MggStatus {
+	if roles.is_null() {
+		return invalid_argument("roles не может быть NULL");
+	}
+	// SAFETY: вызывающая сторона гарантирует размер и доступность массива.
+	let codes = unsafe { std::slice::from_raw_parts_mut(roles, len) };
+
+	let mut parsed = Vec::with_capacity(len);
+	for &code in codes.iter() {
+		match Role::try_from(code) {
+			Ok(role) => parsed.push(role),
+			Err(e) => return fail(e),
+		}
+	}
+
+	shuffle_roles_with_seed(&mut parsed, seed);
+	let Some(shuffled) = parsed.iter().map(Role::code).collect::<Option<Vec<u8>>>() else {
+		return invalid_argument("у пользовательских ролей нет числового кода");
+	};
+	codes.copy_from_slice(&shuffled);
+	MggStatus::Ok
+}
+
+/// Возвращает название роли. Строка освобождается через `mgg_string_free`.
+///
+/// # Safety
+///
+/// `out_name` должен быть действительным указателем для записи.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mgg_role_name(role: u8, out_name: *mut *mut c_char) -> MggStatus {
+	if out_name.is_null() {
+		return invalid_argument("out_name не может быть NULL");
+	}
+	match Role::try_from(role) {
+		Ok(role) => {
+			// SAFETY: указатель проверен выше.
+			unsafe { *out_name = into_c_string(role.get_name()) };
+			MggStatus::Ok
+		}
+		Err(e) => fail(e),
+	}
+}
+
+/// Возвращает описание роли. Строка освобождается через `mgg_string_free`.
+///
+/// # Safety
+///
+/// `out_description` должен быть действительным указателем для записи.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mgg_role_description(role: u8, out_description: *mut *mut c_char) -> MggStatus {
+	if out_description.is_null() {
+		return invalid_argument("out_description не может быть NULL");
+	}
+	match Role::try_from(role) {
+		Ok(role) => {
+			// SAFETY: указатель проверен выше.
+			unsafe { *out_description = into_c_string(role.get_description()) };
+			MggStatus::Ok
+		}
+		Err(e) => fail(e),
+	}
+}
+
+/// Возвращает сторону роли: 0 — мирные жители, 1 — мафия, 2 — одиночка.
+///
+/// # Safety
+///
+/// `out_faction` должен быть действительным указателем для записи.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mgg_role_faction(role: u8, out_faction: *mut u8) -> MggStatus {
+	if out_faction.is_null() {
+		return invalid_argument("out_faction не может быть NULL");
+	}
+	match Role::try_from(role) {
+		Ok(role) => {
+			// SAFETY: указатель проверен выше.
+			unsafe { *out_faction = role.get_faction() as u8 };
+			MggStatus::Ok
+		}
+		Err(e) => fail(e),
+	}
+}
+
+/// Возвращает текст последней ошибки в текущем потоке или NULL, если ошибок не было.
+/// Строка освобождается через `mgg_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn mgg_last_error_message() -> *mut c_char {
+	LAST_ERROR.with(|last| match last.borrow().as_deref() {
+		Some(message) => into_c_string(message),
+		None => ptr::null_mut(),
+	})
+}
+
+/// Освобождает строку, полученную от библиотеки.
+///
+/// # Safety
+///
+/// `text` должен быть получен от библиотеки и ещё не освобождён (или равен NULL).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mgg_string_free(text: *mut c_char) {
+	if text.is_null() {
+		return;
+	}
+	// SAFETY: строка была создана через CString::into_raw.
+	drop(unsafe { CString::from_raw(text) });
+}
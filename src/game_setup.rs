@@ -1,45 +1,135 @@
 /// Перечисление возможных режимов игры.
 /// `Copy` и `Clone` позволят нам легко передавать этот небольшой тип.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum GameMode {
 	Classic,  // Классический режим без доп. ролей
 	Extended, // Расширенный режим с Маньяком
 }
 
+/// Формат, в котором роли сохраняются на диск.
+#[derive(Debug, Copy, Clone)]
+pub enum OutputFormat {
+	Text,               // Открытые текстовые файлы
+	Encrypted,          // Файлы, зашифрованные персональным PIN-кодом игрока
+	Cards(CardOptions), // Карточки для печати
+}
+
 /// Структура для хранения всех настроек текущей игровой сессии.
+/// Новые настройки могут добавляться без нарушения совместимости,
+/// поэтому вне библиотеки конфигурация создаётся через `GameConfig::new`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct GameConfig {
 	pub player_count: u8,
 	pub game_mode: GameMode,
+	/// Наименьшее допустимое количество игроков.
+	pub min_players: u8,
+	/// Наибольшее допустимое количество игроков.
+	pub max_players: u8,
+	/// С какого количества игроков в расширенном режиме появляется Маньяк.
+	pub maniac_min_players: u8,
+	/// Пользовательские роли этой игры (занимают места мирных жителей).
+	pub custom_roles: Vec<Arc<CustomRole>>,
+}
+
+impl GameConfig {
+	/// Создаёт конфигурацию для заданного количества игроков и режима игры.
+	pub fn new(player_count: u8, game_mode: GameMode) -> Self {
+		GameConfig {
+			player_count,
+			game_mode,
+			min_players: DEFAULT_MIN_PLAYERS,
+			max_players: DEFAULT_MAX_PLAYERS,
+			maniac_min_players: DEFAULT_MANIAC_MIN_PLAYERS,
+			custom_roles: Vec::new(),
+		}
+	}
+
+	/// Задаёт допустимый диапазон количества игроков (в пределах от `MIN_PLAYERS` до `MAX_PLAYERS`).
+	pub fn with_player_limits(mut self, min_players: u8, max_players: u8) -> Result<Self, AppError> {
+		if min_players < MIN_PLAYERS || max_players > MAX_PLAYERS || min_players > max_players {
+			return Err(AppError::Config(format!(
+				"диапазон игроков {min_players}-{max_players} должен лежать в пределах {MIN_PLAYERS}-{MAX_PLAYERS}"
+			)));
+		}
+		self.min_players = min_players;
+		self.max_players = max_players;
+		Ok(self)
+	}
+
+	/// Задаёт количество игроков, начиная с которого в расширенном режиме появляется Маньяк.
+	pub fn with_maniac_min_players(mut self, maniac_min_players: u8) -> Self {
+		self.maniac_min_players = maniac_min_players;
+		self
+	}
+
+	/// Задаёт пользовательские роли игры (не больше `MAX_CUSTOM_ROLES`).
+	pub fn with_custom_roles(mut self, custom_roles: Vec<CustomRole>) -> Result<Self, AppError> {
+		if custom_roles.len() > MAX_CUSTOM_ROLES {
+			return Err(AppError::Config(format!("пользовательских ролей не может быть больше {MAX_CUSTOM_ROLES}")));
+		}
+		self.custom_roles = custom_roles.into_iter().map(Arc::new).collect();
+		Ok(self)
+	}
+}
+
+impl GameMode {
+	/// Разбирает название режима (`classic` или `extended`).
+	pub fn parse(value: &str) -> Option<Self> {
+		match value.trim().to_lowercase().as_str() {
+			"classic" => Some(GameMode::Classic),
+			"extended" => Some(GameMode::Extended),
+			_ => None,
+		}
+	}
 }
 
+use crate::cards::CardOptions;
 use crate::error::AppError;
-use crate::role::{Role};
+use crate::role::{CustomRole, Role, MAX_CUSTOM_ROLES};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-
-const MIN_PLAYERS: u8 = 6;
-const MAX_PLAYERS: u8 = 20;
+use rand::SeedableRng;
+use std::sync::Arc;
+
+/// Наименьшее поддерживаемое количество игроков (мини-мафия).
+pub const MIN_PLAYERS: u8 = 4;
+/// Наибольшее поддерживаемое количество игроков (фестивальные игры).
+pub const MAX_PLAYERS: u8 = 60;
+/// Наименьшее количество игроков, если диапазон не задан.
+pub const DEFAULT_MIN_PLAYERS: u8 = 6;
+/// Наибольшее количество игроков, если диапазон не задан.
+pub const DEFAULT_MAX_PLAYERS: u8 = 20;
+/// С какого количества игроков по умолчанию появляется Маньяк.
+pub const DEFAULT_MANIAC_MIN_PLAYERS: u8 = 8;
 
 pub fn shuffle_roles(roles: &mut [Role]) {
 	let mut rng = rand::rng();
 	roles.shuffle(&mut rng);
 }
 
+/// Перемешивает роли детерминированно: одно и то же зерно даёт одинаковый порядок
+/// (в пределах одной версии программы).
+pub fn shuffle_roles_with_seed(roles: &mut [Role], seed: u64) {
+	let mut rng = StdRng::seed_from_u64(seed);
+	roles.shuffle(&mut rng);
+}
+
 /**
  * Динамически определяет и возвращает набор ролей для заданного количества игроков.
  */
 pub fn get_roles_for_players(config: &GameConfig) -> Result<Vec<Role>, AppError> {
 	// Проверяем количество игроков на валидность
-	if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&config.player_count) {
+	if !(config.min_players..=config.max_players).contains(&config.player_count) {
 		return Err(AppError::InvalidPlayerCount {
 			given: config.player_count,
-			min: MIN_PLAYERS,
-			max: MAX_PLAYERS,
+			min: config.min_players,
+			max: config.max_players,
 		});
 	}
 
 	// Используем более эффективный подход для расчета ролей
-	let role_counts = Role::get_role_counts(config.player_count, config.game_mode);
+	let role_counts = Role::get_role_counts(config)?;
 
 	// Создаем вектор ролей из подсчитанных значений
 	let roles = role_counts.to_vec();
@@ -47,108 +137,87 @@ pub fn get_roles_for_players(config: &GameConfig) -> Result<Vec<Role>, AppError>
 	Ok(roles)
 }
 
-pub fn run_headless_mode(
-	player_count: u8,
-	game_mode: GameMode,
-	player_names: Vec<String>
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	use crate::io_handler::write_role_files;
-	use crate::role::Role;
-
-	// Создаем конфигурацию
-	let config = GameConfig { player_count, game_mode };
-
-	// Валидируем количество
-	let mut roles = match get_roles_for_players(&config) {
-		Ok(role_set) => role_set,
-		Err(e) => {
-			return Err(format!("Ошибка конфигурации: {}", e).into());
-		}
-	};
-
-	println!("\nГенерирую {} ролей для ваших игроков...", roles.len());
-
-	// Перемешиваем роли
-	shuffle_roles(&mut roles);
-
-	// Соединяем имена с ролями
-	let players_with_roles: Vec<(String, Role)> = player_names.into_iter().zip(roles).collect();
+/// Результат распределения ролей: игроки в порядке рассадки
+/// и союзники, которых каждый из них знает в начале игры.
+#[derive(Debug, Clone)]
+pub struct Assignment {
+	pub players: Vec<(String, Role)>,
+	/// Для каждого игрока — индексы известных ему союзников.
+	allies: Vec<Vec<usize>>,
+}
 
-	// Записываем файлы
-	match write_role_files(&players_with_roles) {
-		Ok(()) => {
-			println!("\nУспех! Роли сгенерированы и сохранены в папке 'roles'.");
-			println!("Для каждого игрока создан персональный файл. Количество игроков: {}", players_with_roles.len());
-		}
-		Err(e) => {
-			return Err(format!("Ошибка при записи файлов: {}", e).into());
-		}
+impl Assignment {
+	/// Соединяет имена с ролями и вычисляет знание о команде:
+	/// игроки, чьи роли знают союзников, видят друг друга внутри своей стороны.
+	/// Если Донов несколько, мафия делится на семьи, и союзники видны только внутри семьи.
+	pub fn new(names: Vec<String>, roles: Vec<Role>) -> Self {
+		let players: Vec<(String, Role)> = names.into_iter().zip(roles).collect();
+		let families = Self::split_families(&players);
+
+		let allies = players
+			.iter()
+			.enumerate()
+			.map(|(index, (_, role))| {
+				if !role.knows_teammates() {
+					return Vec::new();
+				}
+				players
+					.iter()
+					.enumerate()
+					.filter(|(other, (_, other_role))| {
+						*other != index
+							&& other_role.knows_teammates()
+							&& other_role.get_faction() == role.get_faction()
+							&& families[*other] == families[index]
+					})
+					.map(|(other, _)| other)
+					.collect()
+			})
+			.collect();
+
+		Assignment { players, allies }
 	}
 
-	// Добавляем небольшую задержку, чтобы убедиться, что файлы записались
-	std::thread::sleep(std::time::Duration::from_millis(100));
-
-	Ok(())
-}
-
-pub fn run_interactive_mode(default_game_mode: GameMode) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	use crate::io_handler::{prompt_for_player_count, prompt_for_player_names, write_role_files};
-	use crate::role::Role;
-
-	loop {
-		// Получаем количество игроков
-		let player_count = match prompt_for_player_count() {
-			Ok(count) => count,
-			Err(e) => {
-				// Если ошибка ввода, печатаем ее и начинаем цикл заново
-				eprintln!("Ошибка: {e}. Пожалуйста, попробуйте еще раз.\n");
-				continue; // Переходим к следующей итерации цикла
-			}
-		};
-
-		// Создаем конфигурацию с фиксированным режимом игры
-		let config = GameConfig { player_count, game_mode: default_game_mode };
-
-		// Валидируем количество
-		let mut roles = match get_roles_for_players(&config) {
-			Ok(role_set) => role_set,
-			Err(e) => {
-				// Если количество не подходит, печатаем ошибку и начинаем заново
-				eprintln!("Ошибка: {e}. Пожалуйста, попробуйте еще раз.\n");
-				continue;
-			}
-		};
-
-		// Если все хорошо, получаем имена
-		let names = prompt_for_player_names(config.player_count)?;
-
-		println!("\nГенерирую {} ролей для ваших игроков...", roles.len());
-
-		// Перемешиваем роли
-		shuffle_roles(&mut roles);
-
-		// Соединяем имена с ролями
-		let players_with_roles: Vec<(String, Role)> = names.into_iter().zip(roles).collect();
-
-		// Записываем файлы
-		match write_role_files(&players_with_roles) {
-			Ok(()) => {
-				println!("\nУспех! Роли сгенерированы и сохранены в папке 'roles'.");
-				println!("Для каждого игрока создан персональный файл. Количество игроков: {}", players_with_roles.len());
-			}
-			Err(e) => {
-				eprintln!("Ошибка при записи файлов: {}", e);
-				eprintln!("Пожалуйста, проверьте права доступа к папке и попробуйте снова.");
-				continue;
-			}
-		}
+	// Номер семьи мафии для каждого игрока. Каждый Дон возглавляет свою семью,
+	// остальные знающие союзников игроки распределяются по семьям по очереди в порядке рассадки.
+	fn split_families(players: &[(String, Role)]) -> Vec<usize> {
+		let dons = players.iter().filter(|(_, role)| *role == Role::Don).count().max(1);
+		let mut next_don = 0;
+		let mut next_member = 0;
+		players
+			.iter()
+			.map(|(_, role)| match role {
+				Role::Don => {
+					next_don += 1;
+					next_don - 1
+				}
+				role if role.knows_teammates() => {
+					next_member += 1;
+					(next_member - 1) % dons
+				}
+				_ => 0,
+			})
+			.collect()
+	}
 
-		// --- Все прошло успешно, выходим из цикла и завершаем программу ---
-		println!("\nНажмите Enter для выхода...");
-		let mut buffer = String::new();
-		std::io::stdin().read_line(&mut buffer).unwrap_or_default(); // Ожидаем нажатия Enter
+	/// Возвращает союзников игрока на месте `index` (имя и роль).
+	pub fn known_allies(&self, index: usize) -> Vec<(&str, &Role)> {
+		self.allies[index]
+			.iter()
+			.map(|&other| (self.players[other].0.as_str(), &self.players[other].1))
+			.collect()
+	}
 
-		// Успешный выход из функции и программы
-		return Ok(());
+	/// Возвращает список союзников в виде строки, отмечая Дона, например "Анна, Борис (Дон)".
+	/// Для игроков без известных союзников возвращает пустую строку.
+	pub fn describe_allies(&self, index: usize) -> String {
+		self.known_allies(index)
+			.iter()
+			.map(|(name, role)| match role {
+				Role::Don => format!("{name} (Дон)"),
+				_ => name.to_string(),
+			})
+			.collect::<Vec<_>>()
+			.join(", ")
 	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use fs2::FileExt;
+use crate::paths;
+
+const LOCK_FILE_NAME: &str = "instance.lock";
+// Переменная окружения, которой процесс, удерживающий блокировку, передает её
+// запущенной им копии программы (новой версии после обновления), пока ждет её завершения.
+#[cfg(feature = "self-update")]
+const HANDOVER_ENV: &str = "MAFIA_INSTANCE_HANDOVER";
+
+/// Блокировка единственного экземпляра приложения.
+/// Удерживается, пока значение живо; после аварийного завершения ОС снимает её сама.
+pub struct InstanceLock {
+	file: File,
+}
+
+/// Процесс, записанный в файле блокировки.
+pub struct LockOwner {
+	pub pid: u32,
+	/// Время запуска в секундах Unix.
+	pub started: u64,
+}
+
+/// Причина, по которой не удалось захватить блокировку.
+pub enum InstanceError {
+	/// Блокировку удерживает другой экземпляр (если удалось прочитать, то какой).
+	AlreadyRunning(Option<LockOwner>),
+	Io(io::Error),
+}
+
+impl fmt::Display for InstanceError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			InstanceError::AlreadyRunning(Some(owner)) => {
+				let minutes = unix_now().saturating_sub(owner.started) / 60;
+				write!(f, "Обнаружен запущенный экземпляр приложения (PID {}, запущен {} мин. назад).", owner.pid, minutes)
+			}
+			InstanceError::AlreadyRunning(None) => write!(f, "Обнаружен запущенный экземпляр приложения."),
+			InstanceError::Io(e) => write!(f, "Не удалось открыть файл блокировки: {}", e),
+		}
+	}
+}
+
+impl From<io::Error> for InstanceError {
+	fn from(err: io::Error) -> Self {
+		InstanceError::Io(err)
+	}
+}
+
+impl InstanceLock {
+	/// Захватывает блокировку без ожидания.
+	pub fn acquire() -> Result<Self, InstanceError> {
+		let path = lock_path()?;
+		let file = open_lock_file(&path)?;
+
+		match file.try_lock_exclusive() {
+			Ok(()) => {}
+			// Блокировку ОС держит живой процесс: после завершения процесса она снимается сама.
+			// PID в файле здесь не проверяется: он может быть устаревшим, еще не записанным
+			// или принадлежать другому компьютеру либо пространству имен PID.
+			Err(e) if is_contended(&e) => return Err(InstanceError::AlreadyRunning(read_owner(&file))),
+			// Файловая система не поддерживает блокировки: остается проверка по PID.
+			Err(_) => {
+				if let Some(owner) = read_owner(&file)
+					&& owner.pid != std::process::id()
+					&& is_process_alive(owner.pid)
+				{
+					return Err(InstanceError::AlreadyRunning(Some(owner)));
+				}
+			}
+		}
+
+		Self::hold(file)
+	}
+
+	// Записывает в файл блокировки сведения о текущем процессе.
+	fn hold(mut file: File) -> Result<Self, InstanceError> {
+		file.set_len(0)?;
+		file.seek(SeekFrom::Start(0))?;
+		write!(file, "pid={}\nstarted={}\n", std::process::id(), unix_now())?;
+		file.sync_all()?;
+		Ok(InstanceLock { file })
+	}
+}
+
+impl Drop for InstanceLock {
+	fn drop(&mut self) {
+		// Файл не удаляется: другой процесс мог уже открыть его и ждать блокировку.
+		// Достаточно стереть сведения о владельце, блокировка снимается при закрытии файла.
+		let _ = self.file.set_len(0);
+		let _ = FileExt::unlock(&self.file);
+	}
+}
+
+/**
+ * Передает блокировку текущего процесса запускаемой копии программы: процесс обязуется
+ * дождаться её завершения и до тех пор удерживает блокировку за неё.
+ */
+#[cfg(feature = "self-update")]
+pub fn hand_over(command: &mut std::process::Command) -> &mut std::process::Command {
+	command.env(HANDOVER_ENV, std::process::id().to_string())
+}
+
+/**
+ * Запущена ли программа процессом, который передал ей свою блокировку (см. `hand_over`).
+ */
+#[cfg(feature = "self-update")]
+pub fn is_handed_over() -> bool {
+	std::env::var_os(HANDOVER_ENV).is_some()
+}
+
+// Файл блокировки хранится в каталоге состояния: один экземпляр на пользователя,
+// из какой бы папки программа ни была запущена.
+fn lock_path() -> io::Result<PathBuf> {
+	Ok(paths::ensure(&paths::dirs().state)?.join(LOCK_FILE_NAME))
+}
+
+fn open_lock_file(path: &Path) -> io::Result<File> {
+	OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)
+}
+
+fn is_contended(err: &io::Error) -> bool {
+	err.raw_os_error() == fs2::lock_contended_error().raw_os_error()
+}
+
+// Читает владельца из файла блокировки. В Windows занятый файл прочитать нельзя,
+// но там блокировка и так снимается системой при завершении процесса.
+fn read_owner(mut file: &File) -> Option<LockOwner> {
+	let mut content = String::new();
+	file.seek(SeekFrom::Start(0)).ok()?;
+	file.read_to_string(&mut content).ok()?;
+
+	let mut pid = None;
+	let mut started = None;
+	for line in content.lines() {
+		match line.split_once('=') {
+			Some(("pid", value)) => pid = value.trim().parse().ok(),
+			Some(("started", value)) => started = value.trim().parse().ok(),
+			_ => {}
+		}
+	}
+	Some(LockOwner { pid: pid?, started: started.unwrap_or_default() })
+}
+
+fn unix_now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+// Существует ли процесс с данным PID.
+#[cfg(unix)]
+fn is_process_alive(pid: u32) -> bool {
+	let Ok(pid) = libc::pid_t::try_from(pid) else {
+		return false;
+	};
+	// SAFETY: сигнал 0 ничего не отправляет, а только проверяет существование процесса.
+	if unsafe { libc::kill(pid, 0) } == 0 {
+		return true;
+	}
+	// Процесс другого пользователя существует, но сигналы ему слать нельзя.
+	io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn is_process_alive(pid: u32) -> bool {
+	use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_INVALID_PARAMETER, STILL_ACTIVE};
+	use windows_sys::Win32::System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+	// SAFETY: дескриптор процесса проверяется на NULL и закрывается после использования.
+	unsafe {
+		let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+		if handle.is_null() {
+			// Нет доступа — процесс существует; неверный параметр — процесса нет.
+			return GetLastError() != ERROR_INVALID_PARAMETER;
+		}
+		let mut exit_code = 0;
+		let alive = GetExitCodeProcess(handle, &mut exit_code) != 0 && exit_code == STILL_ACTIVE as u32;
+		CloseHandle(handle);
+		alive
+	}
+}
+
+// На прочих системах проверить процесс нельзя: считаем его запущенным.
+#[cfg(not(any(unix, windows)))]
+fn is_process_alive(_pid: u32) -> bool {
+	true
+}
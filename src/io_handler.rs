@@ -1,51 +1,14 @@
-use crate::role::Role;
-use crate::error::AppError;
+use mafia_game_generator::cards::{CardFormat, CardOptions};
+use mafia_game_generator::error::AppError;
+use mafia_game_generator::game_setup::{Assignment, GameMode, OutputFormat};
 use std::collections::HashSet;
 use std::io::{self, Write};
-use std::fs;
-use std::path::{Component, Path, PathBuf};
 use std::env;
 
 /**
- * Запрашивает у пользователя количество игроков через консоль.
+ * Проверяет имя игрока: непустое, без запрещенных символов и не повторяет уже введенные.
  */
-pub fn prompt_for_player_count() -> Result<u8, AppError> {
-	print!("Введите количество игроков (например, 10): ");
-	io::stdout().flush()?;
-
-	let mut input = String::new();
-	io::stdin().read_line(&mut input)?;
-
-	let trimmed_input = input.trim();
-
-	// Дополнительная валидация входных данных
-	if trimmed_input.is_empty() {
-		// Создаем ошибку парсинга для пустой строки
-		let err = "x".parse::<u8>().unwrap_err();
-		return Err(AppError::ParseInt(err));
-	}
-
-	// Проверка на наличие только цифр
-	if !trimmed_input.chars().all(|c| c.is_ascii_digit()) {
-		// Создаем ошибку парсинга для недопустимых символов
-		let err = "x".parse::<u8>().unwrap_err();
-		return Err(AppError::ParseInt(err));
-	}
-
-	let count = trimmed_input.parse::<u8>()?;
-	Ok(count)
-}
-
-/**
- * Валидирует и получает имя для игрока.
- */
-fn validate_and_get_name(player_index: usize, unique_names: &HashSet<String>) -> Result<String, AppError> {
-	// Бесконечный цикл, который будет повторяться, пока не будет введено корректное имя
-	print!("Введите имя для Игрока {player_index}: ");
-	io::stdout().flush().unwrap_or_default();
-
-	let mut name = String::new();
-	io::stdin().read_line(&mut name).unwrap_or_default();
+pub fn validate_player_name(name: &str, unique_names: &HashSet<String>) -> Result<String, AppError> {
 	let trimmed_name = name.trim();
 
 	// Проверяем все условия
@@ -68,7 +31,7 @@ fn validate_and_get_name(player_index: usize, unique_names: &HashSet<String>) ->
 		return Err(AppError::InvalidCharactersInName(trimmed_name.to_string()));
 	}
 
-	// ИСПРАВЛЕННАЯ ЛОГИКА: Сначала проверяем, потом добавляем.
+	// Имена сравниваются без учета регистра.
 	let normalized_name = trimmed_name.to_lowercase();
 	if unique_names.contains(&normalized_name) {
 		return Err(AppError::DuplicatePlayerName(trimmed_name.to_string()));
@@ -77,175 +40,180 @@ fn validate_and_get_name(player_index: usize, unique_names: &HashSet<String>) ->
 	Ok(trimmed_name.to_string())
 }
 
+/// Ответ на вопрос мастера настройки игры.
+pub enum Answer {
+	/// Вернуться к предыдущему шагу.
+	Back,
+	/// Введенный текст без пробелов по краям (пустой — значение по умолчанию).
+	Value(String),
+}
+
+/// Ввод, означающий возврат к предыдущему шагу.
+pub const BACK_COMMAND: &str = "<";
+
 /**
- * Запрашивает у пользователя имена для каждого игрока.
+ * Задает вопрос мастера настройки и читает ответ.
+ * Конец ввода считается ошибкой, чтобы мастер не повторял вопрос бесконечно.
  */
-pub fn prompt_for_player_names(player_count: u8) -> Result<Vec<String>, AppError> {
-	println!("\n--- Ввод имен игроков ---");
-	let mut names = Vec::with_capacity(player_count as usize);
-	let mut unique_names = HashSet::with_capacity(player_count as usize);
-
-	for i in 0..(player_count as usize) {
-		loop {
-			// Пытаемся получить и валидировать имя
-			match validate_and_get_name(i + 1, &unique_names) {
-				Ok(name) => {
-					// Если успешно, добавляем имя в ОБА списка и выходим из цикла
-					unique_names.insert(name.to_lowercase());
-					names.push(name);
-					break;
-				}
-				Err(e) => {
-					// Если ошибка, печатаем ее и цикл продолжается
-					eprintln!("{e}\nПожалуйста, попробуйте еще раз.");
-				}
-			}
-		}
+pub fn prompt_answer(question: &str) -> Result<Answer, AppError> {
+	print!("{question}: ");
+	io::stdout().flush()?;
+
+	let mut input = String::new();
+	if io::stdin().read_line(&mut input)? == 0 {
+		return Err(AppError::Io(io::Error::new(io::ErrorKind::UnexpectedEof, "ввод завершен")));
 	}
 
-	Ok(names)
+	let answer = input.trim();
+	if answer == BACK_COMMAND || answer.eq_ignore_ascii_case("назад") {
+		return Ok(Answer::Back);
+	}
+	Ok(Answer::Value(answer.to_string()))
 }
 
 /**
- * Очищает имя файла, удаляя или заменяя недопустимые символы.
+ * Запрашивает у пользователя PIN-код через консоль.
  */
-fn sanitize_filename(filename: &str) -> String {
-	// Более строгая санитизация имени файла
-	filename
-		.replace(' ', "_")
-		.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "")
-		.trim_matches(|c: char| c == '.' || c == '_')
-		.chars()
-		.take(50) // Ограничиваем длину имени файла
-		.collect()
+pub fn prompt_for_pin() -> Result<String, AppError> {
+	print!("Введите PIN-код: ");
+	io::stdout().flush()?;
+
+	let mut input = String::new();
+	io::stdin().read_line(&mut input)?;
+
+	Ok(input.trim().to_string())
 }
 
 /**
- * Создает папку "roles" и записывает в нее файлы с ролями для каждого игрока.
+ * Задает пользователю вопрос с ответом да/нет. Пустой ответ означает "нет".
  */
-pub fn write_role_files(players: &[(String, Role)]) -> Result<(), AppError> {
-	let output_dir = "roles";
+pub fn prompt_confirmation(question: &str) -> Result<bool, AppError> {
+	print!("{question} [д/Н]: ");
+	io::stdout().flush()?;
 
-	// Создаем папку `roles`. `create_dir_all` не выдает ошибку, если папка уже существует.
-	if let Err(e) = fs::create_dir_all(output_dir) {
-		return Err(AppError::Io(e));
-	}
+	let mut input = String::new();
+	io::stdin().read_line(&mut input)?;
 
-	// Проходим по вектору с ролями, получая и индекс, и саму роль.
-	for (player_name, role) in players.iter() {
-		// Создаем имя файла на основе имени игрока.
-		// Заменяем пробелы на подчеркивания для надежности.
-		let safe_filename = sanitize_filename(player_name);
+	Ok(matches!(input.trim().to_lowercase().as_str(), "д" | "да" | "y" | "yes"))
+}
 
-		// Дополнительная проверка: имя файла не должно быть пустым
-		if safe_filename.is_empty() {
-			return Err(AppError::InvalidFileName(player_name.clone()));
-		}
+/// Перевод курсора в начало, очистка экрана и затем истории прокрутки.
+/// История стирается последней: иначе часть терминалов сохраняет в ней только что очищенный экран.
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J\x1b[3J";
 
-		let file_path = format!("{output_dir}/{safe_filename}.txt");
+/**
+ * Очищает экран терминала вместе с историей прокрутки, чтобы предыдущий вывод нельзя было пролистать.
+ */
+pub fn clear_screen() -> Result<(), AppError> {
+	enable_escape_sequences();
+	print!("{CLEAR_SCREEN}");
+	io::stdout().flush()?;
+	Ok(())
+}
 
-		// Проверка безопасности: убедитесь, что путь к файлу находится в ожидаемом каталоге.
-		if !is_path_safe(&file_path, output_dir) {
-			return Err(AppError::InvalidFileName(player_name.clone()));
+// Консоль Windows по умолчанию печатает escape-последовательности как текст:
+// включаем их обработку один раз для всего процесса.
+#[cfg(windows)]
+fn enable_escape_sequences() {
+	use std::sync::Once;
+	use windows_sys::Win32::System::Console::{
+		GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING, STD_OUTPUT_HANDLE,
+	};
+
+	static ENABLED: Once = Once::new();
+	// SAFETY: дескриптор стандартного вывода принадлежит процессу и не закрывается;
+	// если вывод перенаправлен не в консоль, GetConsoleMode вернёт ошибку и режим не меняется.
+	ENABLED.call_once(|| unsafe {
+		let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+		let mut mode = 0;
+		if GetConsoleMode(handle, &mut mode) != 0 {
+			SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING);
 		}
+	});
+}
 
-		// Формируем содержимое файла, используя имя игрока.
-		let content = format!(
-			"Игрок: {}\n\nВаша роль: {}\n\nОписание:\n{}\n",
-			player_name,
-			role.get_name(),
-			role.get_description()
-		);
-
-		// И здесь тоже `?` делает код чистым и лаконичным.
-		if let Err(e) = fs::write(&file_path, content) {
-			return Err(AppError::Io(e));
-		}
-	}
+#[cfg(not(windows))]
+fn enable_escape_sequences() {}
 
-	// Проверяем, что файлы действительно созданы
-	match fs::read_dir(output_dir) {
-		Ok(entries) => {
-			let count = entries.count();
-			if count != players.len() {
-				eprintln!("Предупреждение: Ожидается {} файлов, но создано {}", players.len(), count);
-			}
-		}
-		Err(e) => {
-			eprintln!("Предупреждение: Не удалось проверить содержимое папки '{}': {}", output_dir, e);
-		}
-	}
+/**
+ * Ждет нажатия Enter. Конец ввода считается ошибкой.
+ */
+pub fn wait_for_enter(prompt: &str) -> Result<(), AppError> {
+	print!("{prompt}");
+	io::stdout().flush()?;
 
+	let mut buffer = String::new();
+	if io::stdin().read_line(&mut buffer)? == 0 {
+		return Err(AppError::Io(io::Error::new(io::ErrorKind::UnexpectedEof, "ввод завершен")));
+	}
 	Ok(())
 }
 
 /**
- * Проверяет, является ли путь к файлу безопасным и находится ли он в ожидаемом каталоге.
+ * Показывает роли по очереди на одном экране, когда компьютер передают от игрока к игроку.
+ * Перед каждым следующим игроком экран очищается вместе с историей прокрутки.
  */
-fn is_path_safe(file_path: &str, expected_dir: &str) -> bool {
-	let path = Path::new(file_path);
-	let expected_base = Path::new(expected_dir);
-
-	// Абсолютный путь всегда небезопасен в этом контексте.
-	if path.is_absolute() {
-		return false;
-	}
-
-	// Проверяем, что путь начинается с ожидаемой директории
-	if !path.starts_with(expected_base) {
-		return false;
-	}
+pub fn reveal_roles_in_turn(assignment: &Assignment) -> Result<(), AppError> {
+	let players: Vec<usize> = (0..assignment.players.len()).collect();
+	hand_over_in_turn(assignment, &players, |index| {
+		let (name, role) = &assignment.players[index];
+		println!("\n{name}, ваша роль: {} {}\n", role.get_icon(), role.get_name());
+		println!("{}", role.get_description());
+		println!("\nСторона: {}", role.get_faction().get_name());
+		let allies = assignment.describe_allies(index);
+		if !allies.is_empty() {
+			println!("Ваша команда: {allies}");
+		}
+	})?;
 
-	// Создаём нормализованный путь, разрешая компоненты "." и "..".
-	let mut clean_path = PathBuf::new();
-	for component in path.components() {
-		match component {
-			Component::Prefix(_) | Component::RootDir => {
-				// Если компонент превратился в префикс или корень, значит,
-				// ".." вывел нас за пределы `expected_base`.
-				return false;
-			}
-			Component::CurDir => {
-				// Игнорируем "./"
-				continue;
-			}
-			Component::ParentDir => {
-				// Попытка подняться на уровень выше. Это и есть основная проверка.
-				// Если мы не можем "подняться" в clean_path, значит, ".." указывает выше `expected_base`.
-				if !clean_path.pop() {
-					return false; // ".." указывает выше базового каталога
-				}
-			}
-			Component::Normal(c) => {
-				// Обычный компонент пути (файл или каталог), добавляем его.
-				// Дополнительная проверка: имя компонента не должно содержать недопустимых символов
-				let component_str = c.to_string_lossy();
-				if component_str.contains(['/', '\\', '\0']) {
-					return false;
-				}
-				clean_path.push(c);
-			}
+	println!("Все игроки узнали свои роли.");
+	if prompt_confirmation("Показать список ролей ведущему?")? {
+		for (index, (name, role)) in assignment.players.iter().enumerate() {
+			println!("  {:>2}. {} — {}", index + 1, name, role.get_name());
 		}
+		wait_for_enter("\nНажмите Enter, чтобы скрыть список...")?;
+		clear_screen()?;
 	}
+	Ok(())
+}
 
-	// Это гарантирует, что даже после нормализации путь не вышел за пределы базового каталога.
-	if !clean_path.starts_with(expected_base) {
-		return false;
-	}
+/**
+ * Показывает сгенерированные PIN-коды по очереди тем же способом, что и роли:
+ * каждый игрок видит только свой PIN-код. Игроки без сгенерированного PIN-кода (`None`) пропускаются.
+ */
+pub fn reveal_pins_in_turn(assignment: &Assignment, pins: &[Option<String>]) -> Result<(), AppError> {
+	let players: Vec<usize> = (0..assignment.players.len()).filter(|&index| matches!(pins.get(index), Some(Some(_)))).collect();
+	hand_over_in_turn(assignment, &players, |index| {
+		let name = &assignment.players[index].0;
+		let pin = pins[index].as_deref().unwrap_or_default();
+		println!("\n{name}, ваш PIN-код: {pin}");
+		println!("PIN-код показывается только один раз. Он нужен, чтобы открыть файл с вашей ролью.");
+	})?;
+	println!("Все игроки получили свои PIN-коды.");
+	Ok(())
+}
 
-	// Дополнительная проверка: путь должен содержать только один уровень вложенности
-	// (не более одного компонента после expected_dir)
-	let components_after_base: Vec<_> = clean_path
-		.components()
-		.skip(expected_base.components().count())
-		.collect();
+// Передает компьютер по очереди игрокам с индексами `players`: ждет, пока подойдет игрок,
+// показывает ему личные сведения (`show`) и очищает экран перед следующим.
+fn hand_over_in_turn(assignment: &Assignment, players: &[usize], mut show: impl FnMut(usize)) -> Result<(), AppError> {
+	for (turn, &index) in players.iter().enumerate() {
+		clear_screen()?;
+		println!("Игрок {} из {}\n", turn + 1, players.len());
+		wait_for_enter(&format!("Передайте компьютер игроку {} и нажмите Enter...", assignment.players[index].0))?;
 
-	if components_after_base.len() != 1 {
-		return false;
+		show(index);
+		wait_for_enter("\nЗапомните и нажмите Enter, чтобы скрыть...")?;
 	}
+	clear_screen()
+}
 
-	// Если все проверки пройдены, путь считается безопасным.
-	true
+/**
+ * Ожидает нажатия Enter перед выходом, чтобы окно консоли не закрылось сразу.
+ */
+pub fn wait_for_exit() {
+	println!("\nНажмите Enter для выхода...");
+	let mut buffer = String::new();
+	io::stdin().read_line(&mut buffer).unwrap_or_default(); // Ожидаем нажатия Enter
 }
 
 /// Отображает справочную информацию о доступных командах и параметрах запуска приложения.
@@ -254,25 +222,102 @@ pub fn print_help() {
 	println!("Версия: {}", env!("CARGO_PKG_VERSION"));
 	println!();
 	println!("Использование:");
-	println!("  MafiaGameGenerator              - Интерактивный режим (классический режим по умолчанию)");
+	println!("  MafiaGameGenerator              - Интерактивный режим: мастер настройки игры по шагам");
+	#[cfg(feature = "self-update")]
+	println!("  MafiaGameGenerator --no-update-check - Интерактивный режим без проверки обновлений");
+	#[cfg(feature = "tui")]
+	println!("  MafiaGameGenerator --tui        - Полноэкранный интерфейс: таблица игроков, состав ролей и показ ролей");
 	println!("  MafiaGameGenerator --help       - Показать эту справку");
 	println!("  MafiaGameGenerator --version    - Показать версию программы");
-	println!("  MafiaGameGenerator --update     - Проверить обновления");
-	println!("  MafiaGameGenerator --headless <player_count> <game_mode> <player_names...>");
+	println!("  MafiaGameGenerator --paths      - Показать каталоги с настройками, данными и кэшем");
+	println!("  MafiaGameGenerator --config show|path - Показать действующие настройки или путь к файлу настроек");
+	println!("  MafiaGameGenerator --config set <раздел.параметр> <значение> - Изменить параметр в файле настроек");
+	#[cfg(feature = "self-update")]
+	println!("  MafiaGameGenerator --update [--update-source <источник>] [--channel <stable|beta>] - Проверить обновления");
+	#[cfg(feature = "self-update")]
+	println!("  MafiaGameGenerator --rollback   - Вернуть версию, установленную до последнего обновления");
+	println!("  MafiaGameGenerator --headless [--template <file>] [--encrypt | --cards <html|svg>] <player_count> <game_mode> <player_names...>");
+	println!("  MafiaGameGenerator --pass [--save] <player_count> <game_mode> <player_names...> - Показать роли по очереди на этом экране");
+	println!("  MafiaGameGenerator --reveal <file> [PIN] - Показать роль из зашифрованного файла");
+	#[cfg(feature = "serve")]
+	println!("  MafiaGameGenerator --serve [--port <port>] <player_count> <game_mode> <player_names...>");
 	println!();
 	println!("Параметры headless режима:");
-	println!("  player_count  - Количество игроков (6-20)");
+	println!("  player_count  - Количество игроков (4-60; по умолчанию 6-20, диапазон задается в разделе [generation] настроек)");
 	println!("  game_mode     - Режим игры (classic или extended)");
 	println!("  player_names  - Имена игроков (через пробел)");
+	println!("  --template    - Файл шаблона роли. Подстановки: {{player}}, {{role}}, {{description}},");
+	println!("                  {{faction}}, {{teammates}}, {{team}}, {{seat}}, {{date}}");
+	println!("  --encrypt     - Зашифровать файлы ролей PIN-кодами игроков. PIN можно задать");
+	println!("                  в виде \"Имя:1234\", иначе он будет сгенерирован и показан игроку лично");
+	println!("  --cards       - Вместо файлов сохранить карточки для печати на листах A4 (html или svg)");
+	println!("  --folded      - Складные карточки: имя снаружи, роль внутри");
+	println!("  --no-icons    - Не показывать значки ролей на карточках");
+	println!();
+	println!("Режим --pass для игры без телефонов: компьютер передают по кругу, программа называет игрока,");
+	println!("показывает его роль и очищает экран вместе с историей прокрутки перед следующим.");
+	println!("Роли не записываются на диск; с флагом --save файлы сохраняются в формате из настроек.");
+	println!();
+	println!("Одновременно может работать только один экземпляр программы. Для параллельных");
+	println!("запусков из скриптов добавьте флаг --allow-multiple (в любом режиме).");
+	println!();
+	println!("Файлы ролей, карточки и QR-коды сохраняются в каталог данных пользователя (см. --paths).");
+	println!("Каталоги можно переопределить переменными MAFIA_CONFIG_DIR, MAFIA_DATA_DIR,");
+	println!("MAFIA_STATE_DIR и MAFIA_CACHE_DIR.");
+	println!();
+	println!("Значения по умолчанию задаются в файле настроек config.toml (см. --config path):");
+	println!("  [generation]  - game_mode, min_players, max_players, maniac_min_players");
+	println!("  [output]      - format (text, encrypted, cards), cards (html, svg), folded, icons,");
+	println!("                  directory, template");
+	println!("  [locale]      - language, date_format (dd.mm.yyyy или yyyy-mm-dd)");
+	#[cfg(feature = "self-update")]
+	println!("  [update]      - source, channel, auto_check");
+	println!("  [[roles]]     - Пользовательские роли: name, description, faction (town, mafia, neutral),");
+	println!("                  count, min_players, icon");
+	println!("Приоритет: параметры командной строки, затем переменные окружения (MAFIA_GAME_MODE,");
+	println!("MAFIA_OUTPUT_FORMAT, MAFIA_OUTPUT_DIR), затем файл настроек, затем встроенные значения.");
+	println!("Пример: MafiaGameGenerator --config set generation.game_mode extended");
+	#[cfg(feature = "serve")]
+	{
+		println!();
+		println!("Режим --serve запускает локальный веб-сервер: каждый игрок получает персональную ссылку");
+		println!("и один раз смотрит свою роль с телефона. Роли не записываются на диск, интернет не нужен.");
+		println!("  --port        - Порт сервера (по умолчанию {})", crate::server::DEFAULT_PORT);
+	}
+	#[cfg(feature = "self-update")]
+	{
+		println!();
+		println!("Источник обновлений (--update-source или переменная окружения MAFIA_UPDATE_SOURCE):");
+		println!("  github              - Релизы на GitHub (по умолчанию)");
+		println!("  http(s)://адрес     - Зеркало с файлом latest.json и файлами релиза");
+		println!("  file://папка        - Папка с той же структурой, например сетевой диск");
+		println!("В интерактивном режиме обновления проверяются в фоне не чаще раза в сутки, установка");
+		println!("предлагается после генерации. Отключить: --no-update-check или MAFIA_NO_UPDATE_CHECK=1.");
+		println!("Канал обновлений (--channel или переменная окружения MAFIA_UPDATE_CHANNEL):");
+		println!("  stable              - Только стабильные релизы (по умолчанию)");
+		println!("  beta                - Также пре-релизы для тестирования (в зеркале: latest-beta.json)");
+		println!("Новая версия после установки проходит самопроверку; если она не запустилась,");
+		println!("при следующем запуске автоматически восстанавливается предыдущая версия.");
+	}
 	println!();
 	println!("Пример:");
 	println!("  MafiaGameGenerator --headless 6 classic \"Игрок1\" \"Игрок2\" \"Игрок3\" \"Игрок4\" \"Игрок5\" \"Игрок6\"");
 	println!("  MafiaGameGenerator --headless 8 extended \"Игрок1\" \"Игрок2\" \"Игрок3\" \"Игрок4\" \"Игрок5\" \"Игрок6\" \"Игрок7\" \"Игрок8\"");
+	#[cfg(feature = "serve")]
+	println!("  MafiaGameGenerator --serve --port 8080 6 classic \"Игрок1\" \"Игрок2\" \"Игрок3\" \"Игрок4\" \"Игрок5\" \"Игрок6\"");
+}
+
+/// Флаг, разрешающий запуск нескольких экземпляров (например, из скриптов).
+const ALLOW_MULTIPLE_FLAG: &str = "--allow-multiple";
+
+/// Разрешен ли запуск параллельно с другими экземплярами. Флаг допустим в любой позиции.
+pub fn allow_multiple_instances() -> bool {
+	env::args().skip(1).any(|arg| arg == ALLOW_MULTIPLE_FLAG)
 }
 
 /// Анализирует аргументы командной строки и возвращает соответствующее действие.
 pub fn parse_arguments() -> CliAction {
-	let args: Vec<String> = env::args().collect();
+	let args: Vec<String> = env::args().filter(|arg| arg != ALLOW_MULTIPLE_FLAG).collect();
 
 	// Проверить флаг помощи.
 	if args.len() > 1 && (args[1] == "--help" || args[1] == "-h") {
@@ -284,60 +329,253 @@ pub fn parse_arguments() -> CliAction {
 		return CliAction::ShowVersion;
 	}
 
+	// Проверить флаг вывода каталогов.
+	if args.len() > 1 && args[1] == "--paths" {
+		return CliAction::ShowPaths;
+	}
+
+	// Проверить команду работы с файлом настроек.
+	if args.len() > 1 && args[1] == "--config" {
+		return match args.get(2).map(String::as_str) {
+			Some("show") => CliAction::ConfigShow,
+			Some("path") => CliAction::ConfigPath,
+			Some("set") => match (args.get(3), args.get(4)) {
+				(Some(key), Some(value)) => CliAction::ConfigSet { key: key.clone(), value: value.clone() },
+				_ => CliAction::Error("Использование: --config set <раздел.параметр> <значение>".to_string()),
+			},
+			_ => CliAction::Error("Использование: --config show | set <раздел.параметр> <значение> | path".to_string()),
+		};
+	}
+
 	// Проверить флаг обновления.
 	if args.len() > 1 && args[1] == "--update" {
-		return CliAction::CheckUpdate;
+		#[cfg(feature = "self-update")]
+		{
+			let mut rest = args[2..].to_vec();
+			let source = take_option(&mut rest, "--update-source");
+			let channel = take_option(&mut rest, "--channel");
+			return CliAction::CheckUpdate { source, channel };
+		}
+		#[cfg(not(feature = "self-update"))]
+		return CliAction::Error("Эта сборка не поддерживает обновление (собрана без функции self-update).".to_string());
 	}
 
-	if args.len() > 1 && args[1] == "--headless" {
-		// Проверка аргументов.
-		if args.len() < 5 {
-			return CliAction::Error("Недостаточно аргументов для headless режима. Используйте --help для справки.".to_string());
-		}
+	// Проверить флаг отката обновления.
+	if args.len() > 1 && args[1] == "--rollback" {
+		#[cfg(feature = "self-update")]
+		return CliAction::Rollback;
+		#[cfg(not(feature = "self-update"))]
+		return CliAction::Error("Эта сборка не поддерживает обновление (собрана без функции self-update).".to_string());
+	}
 
-		let player_count = match args[2].parse::<u8>() {
-			Ok(count) => count,
-			Err(_) => {
-				return CliAction::Error(format!("Неверное количество игроков: {}", args[2]));
+	if args.len() > 1 && args[1] == "--headless" {
+		let mut rest = args[2..].to_vec();
+
+		let template_path = take_option(&mut rest, "--template");
+		let encrypt = take_flag(&mut rest, "--encrypt");
+		let folded = take_flag(&mut rest, "--folded");
+		let icons = !take_flag(&mut rest, "--no-icons");
+		let card_format = match take_option(&mut rest, "--cards").as_deref() {
+			None => None,
+			Some("html") => Some(CardFormat::Html),
+			Some("svg") => Some(CardFormat::Svg),
+			Some(other) => {
+				return CliAction::Error(format!("Неверный формат карточек: {}. Допустимые значения: html, svg", other));
 			}
 		};
 
-		let game_mode = match args[3].as_str() {
-			"classic" => crate::game_setup::GameMode::Classic,
-			"extended" => crate::game_setup::GameMode::Extended,
-			_ => {
-				return CliAction::Error(format!("Неверный режим игры: {}. Допустимые значения: classic, extended", args[3]));
+		let output_format = match (encrypt, card_format) {
+			(true, Some(_)) => {
+				return CliAction::Error("Параметры --encrypt и --cards нельзя использовать одновременно.".to_string());
 			}
+			(true, None) => Some(OutputFormat::Encrypted),
+			(false, Some(format)) => Some(OutputFormat::Cards(CardOptions { format, folded, icons })),
+			// Формат не указан: он берется из файла настроек.
+			(false, None) => None,
 		};
 
-		// Имена игроков начинаются с индекса 4
-		let player_names: Vec<String> = args[4..].to_vec();
+		return match parse_game_arguments(&rest) {
+			Ok((player_count, game_mode, player_names)) => CliAction::RunHeadless {
+				player_count,
+				game_mode,
+				player_names,
+				output_format,
+				template_path,
+			},
+			Err(msg) => CliAction::Error(msg),
+		};
+	}
 
-		if player_names.len() != player_count as usize {
-			return CliAction::Error(format!("Количество предоставленных имен игроков ({}) не соответствует указанному количеству игроков ({})",
-				player_names.len(), player_count));
+	if args.len() > 1 && args[1] == "--pass" {
+		let mut rest = args[2..].to_vec();
+		let save_files = take_flag(&mut rest, "--save");
+
+		return match parse_game_arguments(&rest) {
+			Ok((player_count, game_mode, player_names)) => CliAction::RunPass {
+				player_count,
+				game_mode,
+				player_names,
+				save_files,
+			},
+			Err(msg) => CliAction::Error(msg),
+		};
+	}
+
+	if args.len() > 1 && args[1] == "--reveal" {
+		if args.len() < 3 {
+			return CliAction::Error("Не указан файл роли. Используйте --help для справки.".to_string());
 		}
+		return CliAction::Reveal {
+			path: args[2].clone(),
+			pin: args.get(3).cloned(),
+		};
+	}
+
+	// Проверить флаг полноэкранного интерфейса.
+	if args.len() > 1 && args[1] == "--tui" {
+		#[cfg(feature = "tui")]
+		return CliAction::RunTui;
+		#[cfg(not(feature = "tui"))]
+		return CliAction::Error("Эта сборка не поддерживает режим --tui (собрана без функции tui).".to_string());
+	}
+
+	#[cfg(not(feature = "serve"))]
+	if args.len() > 1 && args[1] == "--serve" {
+		return CliAction::Error("Эта сборка не поддерживает режим --serve (собрана без функции serve).".to_string());
+	}
+
+	#[cfg(feature = "serve")]
+	if args.len() > 1 && args[1] == "--serve" {
+		let mut rest = args[2..].to_vec();
+
+		let port = match take_option(&mut rest, "--port") {
+			Some(value) => match value.parse::<u16>() {
+				Ok(port) => port,
+				Err(_) => {
+					return CliAction::Error(format!("Неверный номер порта: {}", value));
+				}
+			},
+			None => crate::server::DEFAULT_PORT,
+		};
 
-		return CliAction::RunHeadless {
-			player_count,
-			game_mode,
-			player_names,
+		return match parse_game_arguments(&rest) {
+			Ok((player_count, game_mode, player_names)) => CliAction::RunServe {
+				player_count,
+				game_mode,
+				player_names,
+				port,
+			},
+			Err(msg) => CliAction::Error(msg),
 		};
 	}
 
-	CliAction::RunInteractive
+	CliAction::RunInteractive {
+		update_check: !args.iter().any(|arg| arg == "--no-update-check"),
+	}
+}
+
+/// Разбирает общие аргументы режимов генерации: количество игроков, режим игры и имена.
+fn parse_game_arguments(args: &[String]) -> Result<(u8, GameMode, Vec<String>), String> {
+	// Проверка аргументов.
+	if args.len() < 3 {
+		return Err("Недостаточно аргументов для генерации ролей. Используйте --help для справки.".to_string());
+	}
+
+	let player_count = match args[0].parse::<u8>() {
+		Ok(count) => count,
+		Err(_) => {
+			return Err(format!("Неверное количество игроков: {}", args[0]));
+		}
+	};
+
+	let game_mode = match args[1].as_str() {
+		"classic" => GameMode::Classic,
+		"extended" => GameMode::Extended,
+		_ => {
+			return Err(format!("Неверный режим игры: {}. Допустимые значения: classic, extended", args[1]));
+		}
+	};
+
+	// Имена игроков идут после режима игры
+	let player_names: Vec<String> = args[2..].to_vec();
+
+	if player_names.len() != player_count as usize {
+		return Err(format!("Количество предоставленных имен игроков ({}) не соответствует указанному количеству игроков ({})",
+			player_names.len(), player_count));
+	}
+
+	Ok((player_count, game_mode, player_names))
+}
+
+/// Извлекает из списка аргументов параметр вида `--name значение` и удаляет его.
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+	let index = args.iter().position(|arg| arg == name)?;
+	if index + 1 >= args.len() {
+		args.remove(index);
+		return None;
+	}
+	let value = args.remove(index + 1);
+	args.remove(index);
+	Some(value)
+}
+
+/// Извлекает из списка аргументов флаг без значения и удаляет его.
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+	match args.iter().position(|arg| arg == name) {
+		Some(index) => {
+			args.remove(index);
+			true
+		}
+		None => false,
+	}
 }
 
 /// Перечисление возможных действий CLI
 pub enum CliAction {
 	ShowHelp,
 	ShowVersion,
-	CheckUpdate,
+	ShowPaths,
+	ConfigShow,
+	ConfigSet {
+		key: String,
+		value: String,
+	},
+	ConfigPath,
+	#[cfg(feature = "self-update")]
+	CheckUpdate {
+		source: Option<String>,
+		channel: Option<String>,
+	},
+	#[cfg(feature = "self-update")]
+	Rollback,
 	RunHeadless {
 		player_count: u8,
-		game_mode: crate::game_setup::GameMode,
+		game_mode: GameMode,
 		player_names: Vec<String>,
+		output_format: Option<OutputFormat>,
+		template_path: Option<String>,
+	},
+	RunPass {
+		player_count: u8,
+		game_mode: GameMode,
+		player_names: Vec<String>,
+		save_files: bool,
+	},
+	Reveal {
+		path: String,
+		pin: Option<String>,
+	},
+	#[cfg(feature = "serve")]
+	RunServe {
+		player_count: u8,
+		game_mode: GameMode,
+		player_names: Vec<String>,
+		port: u16,
+	},
+	#[cfg(feature = "tui")]
+	RunTui,
+	RunInteractive {
+		update_check: bool,
 	},
-	RunInteractive,
 	Error(String),
 }
\ No newline at end of file
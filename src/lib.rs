@@ -0,0 +1,22 @@
+//! Библиотека генератора ролей для игры в мафию.
+//!
+//! Содержит расчёт и распределение ролей, перемешивание и запись результатов
+//! (текстовые и зашифрованные файлы, карточки для печати, QR-коды).
+//! Консольный ввод и обновление приложения находятся в исполняемом файле.
+
+pub mod role;
+pub mod game_setup;
+pub mod error;
+pub mod output;
+pub mod template;
+pub mod cards;
+pub mod crypto;
+pub mod qr;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+pub use error::AppError;
+pub use game_setup::{get_roles_for_players, shuffle_roles, shuffle_roles_with_seed, Assignment, GameConfig, GameMode, OutputFormat};
+pub use role::{Faction, Role, RoleCounts};
@@ -1,24 +1,52 @@
-mod role;
-mod game_setup;
+mod config;
 mod io_handler;
-mod error;
+mod instance;
+mod paths;
+mod session;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(feature = "self-update")]
 mod updater;
+#[cfg(feature = "self-update")]
+mod version;
+mod wizard;
+#[cfg(feature = "serve")]
+mod server;
 
-use std::{env};
-
-#[tokio::main]
-async fn main() {
+fn main() {
 	println!("--- {} ---", env!("CARGO_PKG_NAME"));
 
-	// CПроверить, запущен ли другой экземпляр.
-	if !updater::is_another_instance_running() {
-		eprintln!("Обнаружен запущенный экземпляр приложения. Завершение работы.");
-		return;
-	}
+	// Не допустить запуска второго экземпляра. Блокировка удерживается до выхода из main.
+	// Новую версию после обновления запускает процесс, который уже держит блокировку
+	// и ждет её завершения, поэтому она блокировку не захватывает.
+	#[cfg(feature = "self-update")]
+	let handed_over = instance::is_handed_over();
+	#[cfg(not(feature = "self-update"))]
+	let handed_over = false;
+
+	let _instance_lock = if io_handler::allow_multiple_instances() || handed_over {
+		None
+	} else {
+		match instance::InstanceLock::acquire() {
+			Ok(lock) => Some(lock),
+			Err(e @ instance::InstanceError::AlreadyRunning(_)) => {
+				eprintln!("{e} Завершение работы.");
+				eprintln!("Для параллельных запусков из скриптов используйте флаг --allow-multiple.");
+				return;
+			}
+			Err(e) => {
+				eprintln!("Предупреждение: {e}");
+				None
+			}
+		}
+	};
 
-	// Удалить старую версию, если она существует (от предыдущего обновления)
-	if let Err(e) = updater::cleanup_old_version() {
-		eprintln!("Предупреждение: Не удалось очистить старую версию: {}", e);
+	// Подтвердить или откатить обновление, начатое предыдущим запуском.
+	// Это делается под блокировкой, чтобы обычный запуск во время самопроверки
+	// новой версии не откатил обновление.
+	#[cfg(feature = "self-update")]
+	if let Err(e) = updater::finish_pending_update() {
+		eprintln!("Предупреждение: Не удалось завершить обновление: {}", e);
 	}
 
 	// Анализ аргументов командной строки.
@@ -26,48 +54,163 @@ async fn main() {
 		io_handler::CliAction::ShowHelp => {
 			// Вывод информации о программе.
 			io_handler::print_help();
-			return;
 		}
 		io_handler::CliAction::ShowVersion => {
 			// Вывод версии программы.
 			println!("MafiaGameGenerator v{}", env!("CARGO_PKG_VERSION"));
-			return;
 		}
-		io_handler::CliAction::CheckUpdate => {
-			match updater::check_for_update().await {
+		io_handler::CliAction::ShowPaths => {
+			// Вывод каталогов с настройками и данными.
+			paths::print_dirs();
+		}
+		io_handler::CliAction::ConfigShow => {
+			if let Err(e) = config::show() {
+				eprintln!("{e}");
+			}
+		}
+		io_handler::CliAction::ConfigSet { key, value } => {
+			if let Err(e) = config::set(&key, &value) {
+				eprintln!("{e}");
+			}
+		}
+		io_handler::CliAction::ConfigPath => {
+			// Путь выводится и при ошибке в файле, чтобы его было проще исправить.
+			println!("{}", config::config_path().display());
+		}
+		#[cfg(feature = "self-update")]
+		io_handler::CliAction::CheckUpdate { source, channel } => {
+			let Some(settings) = load_config() else {
+				return;
+			};
+			let result = updater::UpdateSettings::resolve(source.as_deref(), channel.as_deref(), &settings)
+				.map_err(|e| e.into())
+				.and_then(|settings| updater::check_for_update_blocking(&settings));
+			match result {
 				Ok(()) => println!("Проверка обновлений завершена."),
-				Err(e) => eprintln!("Ошибка при проверке обновлений: {}", e),
+				Err(e) => {
+					eprintln!("Ошибка при проверке обновлений: {}", e);
+					// Скрипты должны видеть, что обновление не установлено.
+					drop(_instance_lock);
+					std::process::exit(1);
+				}
+			}
+		}
+		#[cfg(feature = "self-update")]
+		io_handler::CliAction::Rollback => {
+			if let Err(e) = updater::rollback_to_previous_version() {
+				eprintln!("Ошибка при откате обновления: {}", e);
 			}
-			return;
 		}
-		io_handler::CliAction::RunHeadless { player_count, game_mode, player_names } => {
-			if let Err(e) = game_setup::run_headless_mode(player_count, game_mode, player_names) {
+		io_handler::CliAction::RunHeadless { player_count, game_mode, player_names, output_format, template_path } => {
+			let Some(settings) = load_config() else {
+				return;
+			};
+			if let Err(e) = session::run_headless_mode(&settings, player_count, game_mode, player_names, output_format, template_path) {
 				eprintln!("\nКритическая ошибка: {e}");
 				if let Some(source) = e.source() {
 					eprintln!("  Источник: {source}");
 				}
 			}
-			return;
 		}
-		io_handler::CliAction::RunInteractive => {
-			match updater::check_for_update().await {
-				Ok(()) => {}
+		io_handler::CliAction::RunPass { player_count, game_mode, player_names, save_files } => {
+			let Some(settings) = load_config() else {
+				return;
+			};
+			if let Err(e) = session::run_pass_mode(&settings, player_count, game_mode, player_names, save_files) {
+				eprintln!("\nКритическая ошибка: {e}");
+				if let Some(source) = e.source() {
+					eprintln!("  Источник: {source}");
+				}
+			}
+		}
+		io_handler::CliAction::Reveal { path, pin } => {
+			let pin = match pin {
+				Some(pin) => pin,
+				None => match io_handler::prompt_for_pin() {
+					Ok(pin) => pin,
+					Err(e) => {
+						eprintln!("{e}");
+						return;
+					}
+				},
+			};
+			match mafia_game_generator::output::read_encrypted_role_file(&path, &pin) {
+				Ok(content) => println!("\n{content}"),
+				Err(e) => eprintln!("{e}"),
+			}
+		}
+		#[cfg(feature = "serve")]
+		io_handler::CliAction::RunServe { player_count, game_mode, player_names, port } => {
+			let Some(settings) = load_config() else {
+				return;
+			};
+			if let Err(e) = session::run_serve_mode(&settings, player_count, game_mode, player_names, port) {
+				eprintln!("\nКритическая ошибка: {e}");
+				if let Some(source) = e.source() {
+					eprintln!("  Источник: {source}");
+				}
+			}
+		}
+		#[cfg(feature = "tui")]
+		io_handler::CliAction::RunTui => {
+			let Some(settings) = load_config() else {
+				return;
+			};
+			if let Err(e) = tui::run(&settings) {
+				eprintln!("\nКритическая ошибка: {e}");
+			}
+		}
+		io_handler::CliAction::RunInteractive { update_check } => {
+			let Some(settings) = load_config() else {
+				io_handler::wait_for_exit();
+				return;
+			};
+
+			// Обновления проверяются в фоне, пока ведущий вводит данные.
+			#[cfg(feature = "self-update")]
+			let background_check = match updater::UpdateSettings::resolve(None, None, &settings) {
+				Ok(settings) if update_check => updater::spawn_background_check(settings),
+				Ok(_) => None,
 				Err(e) => {
 					eprintln!("Ошибка при проверке обновлений: {}", e);
+					None
 				}
-			}
+			};
+			#[cfg(not(feature = "self-update"))]
+			let _ = update_check;
 
-			if let Err(e) = game_setup::run_interactive_mode(game_setup::GameMode::Classic) {
+			if let Err(e) = session::run_interactive_mode(&settings) {
 				eprintln!("\nКритическая ошибка: {e}");
 				if let Some(source) = e.source() {
 					eprintln!("  Источник: {source}");
 				}
+				return;
 			}
-			return;
+
+			#[cfg(feature = "self-update")]
+			if let Some(check) = background_check
+				&& let Err(e) = check.offer_update()
+			{
+				eprintln!("Ошибка при установке обновления: {}", e);
+			}
+
+			io_handler::wait_for_exit();
 		}
 		io_handler::CliAction::Error(msg) => {
 			eprintln!("{}", msg);
-			return;
 		}
 	}
-}
\ No newline at end of file
+}
+/**
+ * Загружает файл настроек. При ошибке выводит её и возвращает `None`.
+ */
+fn load_config() -> Option<config::Config> {
+	match config::load() {
+		Ok(config) => Some(config),
+		Err(e) => {
+			eprintln!("{e}");
+			eprintln!("Исправьте файл настроек: {}", config::config_path().display());
+			None
+		}
+	}
+}
@@ -0,0 +1,252 @@
+use crate::cards::{self, CardFormat, CardOptions};
+use crate::error::AppError;
+use crate::game_setup::Assignment;
+use crate::template::{RoleTemplate, TemplateContext};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Расширения файлов ролей: открытых и зашифрованных.
+const ROLE_FILE_EXTENSIONS: [&str; 2] = ["txt", "enc"];
+
+/// Предупреждение при записи файлов ролей: файлы записаны, но результат стоит проверить.
+/// Библиотека ничего не выводит сама, сообщать о предупреждениях — дело вызывающей стороны.
+#[derive(Debug)]
+pub enum OutputWarning {
+	/// Не удалось прочитать каталог вывода для проверки.
+	UnreadableDir { dir: PathBuf, error: io::Error },
+}
+
+impl fmt::Display for OutputWarning {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			OutputWarning::UnreadableDir { dir, error } => {
+				write!(f, "Не удалось проверить содержимое папки '{}': {}", dir.display(), error)
+			}
+		}
+	}
+}
+
+/**
+ * Очищает имя файла, удаляя или заменяя недопустимые символы.
+ */
+fn sanitize_filename(filename: &str) -> String {
+	// Более строгая санитизация имени файла
+	filename
+		.replace(' ', "_")
+		.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "")
+		.trim_matches(|c: char| c == '.' || c == '_')
+		.chars()
+		.take(50) // Ограничиваем длину имени файла
+		.collect()
+}
+
+/**
+ * Создает папку `output_dir` и записывает в нее файлы с ролями для каждого игрока.
+ * Файлы ролей прошлой игры (открытые и зашифрованные) перед этим удаляются.
+ * Возвращает предупреждения проверки каталога вывода.
+ */
+pub fn write_role_files(output_dir: &Path, assignment: &Assignment, template: &RoleTemplate) -> Result<Vec<OutputWarning>, AppError> {
+	// Создаем папку. `create_dir_all` не выдает ошибку, если папка уже существует.
+	if let Err(e) = fs::create_dir_all(output_dir) {
+		return Err(AppError::Io(e));
+	}
+	remove_role_files(output_dir)?;
+
+	// Проходим по вектору с ролями, получая и индекс, и саму роль.
+	for (index, (player_name, _)) in assignment.players.iter().enumerate() {
+		let file_path = role_file_path(output_dir, player_name, "txt")?;
+
+		// Формируем содержимое файла по шаблону.
+		let content = format_role_file(assignment, index, template);
+
+		// И здесь тоже `?` делает код чистым и лаконичным.
+		if let Err(e) = fs::write(&file_path, content) {
+			return Err(AppError::Io(e));
+		}
+	}
+
+	verify_output_dir(output_dir, "txt", assignment.players.len())
+}
+
+/**
+ * Создает папку `output_dir` и записывает в нее зашифрованные файлы ролей.
+ * Каждый файл шифруется персональным PIN-кодом игрока (`pins` идут в том же порядке, что и игроки).
+ * Файлы ролей прошлой игры удаляются, чтобы рядом с зашифрованными не осталось открытых.
+ * Возвращает предупреждения проверки каталога вывода.
+ */
+pub fn write_encrypted_role_files(
+	output_dir: &Path,
+	assignment: &Assignment,
+	pins: &[String],
+	template: &RoleTemplate,
+) -> Result<Vec<OutputWarning>, AppError> {
+	fs::create_dir_all(output_dir)?;
+	remove_role_files(output_dir)?;
+
+	for (index, ((player_name, _), pin)) in assignment.players.iter().zip(pins).enumerate() {
+		let file_path = role_file_path(output_dir, player_name, "enc")?;
+
+		let content = format_role_file(assignment, index, template);
+		let encrypted = crate::crypto::encrypt_with_pin(content.as_bytes(), pin)?;
+
+		fs::write(&file_path, encrypted)?;
+	}
+
+	verify_output_dir(output_dir, "enc", assignment.players.len())
+}
+
+/**
+ * Создает папку `output_dir` и записывает в нее карточки ролей для печати:
+ * один HTML-файл или по одному SVG-файлу на каждый лист A4.
+ */
+pub fn write_card_files(output_dir: &Path, assignment: &Assignment, options: &CardOptions) -> Result<Vec<PathBuf>, AppError> {
+	fs::create_dir_all(output_dir)?;
+
+	let mut written = Vec::new();
+	match options.format {
+		CardFormat::Html => {
+			let file_path = output_dir.join("cards.html");
+			fs::write(&file_path, cards::render_html(assignment, options))?;
+			written.push(file_path);
+		}
+		CardFormat::Svg => {
+			// Удаляем листы от предыдущего запуска, чтобы не напечатать лишние карточки.
+			for entry in fs::read_dir(output_dir)?.flatten() {
+				let name = entry.file_name().to_string_lossy().to_string();
+				if name.starts_with("cards_") && name.ends_with(".svg") {
+					fs::remove_file(entry.path())?;
+				}
+			}
+
+			for (index, page) in cards::render_svg_pages(assignment, options).iter().enumerate() {
+				let file_path = output_dir.join(format!("cards_{}.svg", index + 1));
+				fs::write(&file_path, page)?;
+				written.push(file_path);
+			}
+		}
+	}
+
+	Ok(written)
+}
+
+/**
+ * Читает зашифрованный файл роли и расшифровывает его PIN-кодом.
+ */
+pub fn read_encrypted_role_file(path: &str, pin: &str) -> Result<String, AppError> {
+	let data = fs::read(path)?;
+	let plaintext = crate::crypto::decrypt_with_pin(&data, pin)?;
+	String::from_utf8(plaintext).map_err(|_| AppError::Encryption("содержимое файла повреждено".to_string()))
+}
+
+/**
+ * Формирует текст файла роли для игрока на месте `index` по шаблону.
+ */
+fn format_role_file(assignment: &Assignment, index: usize, template: &RoleTemplate) -> String {
+	let (player_name, role) = &assignment.players[index];
+	template.render(&TemplateContext {
+		player: player_name,
+		role: role.clone(),
+		seat: index + 1,
+		teammates: assignment.describe_allies(index),
+	})
+}
+
+/**
+ * Строит безопасный путь к файлу игрока в каталоге вывода.
+ */
+fn role_file_path(output_dir: &Path, player_name: &str, extension: &str) -> Result<PathBuf, AppError> {
+	// Создаем имя файла на основе имени игрока.
+	// Заменяем пробелы на подчеркивания для надежности.
+	let safe_filename = sanitize_filename(player_name);
+
+	// Дополнительная проверка: имя файла не должно быть пустым
+	if safe_filename.is_empty() {
+		return Err(AppError::InvalidFileName(player_name.to_string()));
+	}
+
+	let file_path = output_dir.join(format!("{safe_filename}.{extension}"));
+
+	// Проверка безопасности: убедитесь, что путь к файлу находится в ожидаемом каталоге.
+	if !is_path_safe(&file_path, output_dir) {
+		return Err(AppError::InvalidFileName(player_name.to_string()));
+	}
+
+	Ok(file_path)
+}
+
+/**
+ * Удаляет из каталога вывода файлы ролей прошлой игры.
+ */
+fn remove_role_files(output_dir: &Path) -> Result<(), AppError> {
+	for entry in fs::read_dir(output_dir)?.flatten() {
+		let path = entry.path();
+		let is_role_file = path
+			.extension()
+			.is_some_and(|extension| ROLE_FILE_EXTENSIONS.iter().any(|known| extension == *known));
+		if is_role_file && entry.file_type()?.is_file() {
+			fs::remove_file(path)?;
+		}
+	}
+	Ok(())
+}
+
+/**
+ * Проверяет, что в каталоге вывода ровно столько файлов ролей с расширением `extension`, сколько игроков.
+ * Несовпадение — ошибка: значит, файлы нескольких игроков записались под одним именем.
+ */
+fn verify_output_dir(output_dir: &Path, extension: &str, expected: usize) -> Result<Vec<OutputWarning>, AppError> {
+	match fs::read_dir(output_dir) {
+		Ok(entries) => {
+			let found = entries
+				.flatten()
+				.filter(|entry| entry.path().extension().is_some_and(|ext| ext == extension))
+				.count();
+			if found != expected {
+				return Err(AppError::OutputFileCount { expected, found });
+			}
+			Ok(Vec::new())
+		}
+		Err(error) => Ok(vec![OutputWarning::UnreadableDir { dir: output_dir.to_path_buf(), error }]),
+	}
+}
+
+/**
+ * Создает папку `output_dir` и записывает в нее QR-коды с персональными ссылками игроков
+ * в форматах PNG и SVG.
+ */
+pub fn write_qr_files(output_dir: &Path, players: &[(String, String)]) -> Result<(), AppError> {
+	fs::create_dir_all(output_dir)?;
+
+	for (player_name, url) in players.iter() {
+		let png_path = role_file_path(output_dir, player_name, "png")?;
+		let svg_path = role_file_path(output_dir, player_name, "svg")?;
+
+		fs::write(&png_path, crate::qr::render_png(url)?)?;
+		fs::write(&svg_path, crate::qr::render_svg(url)?)?;
+	}
+
+	Ok(())
+}
+
+/**
+ * Проверяет, что путь к файлу указывает ровно на один файл внутри ожидаемого каталога.
+ * Сам каталог может быть абсолютным (например, каталог данных пользователя),
+ * а имя файла не должно содержать разделителей и переходов "..".
+ */
+fn is_path_safe(file_path: &Path, expected_dir: &Path) -> bool {
+	// Файл должен лежать непосредственно в ожидаемом каталоге.
+	if file_path.parent() != Some(expected_dir) {
+		return false;
+	}
+
+	// Последний компонент пути — обычное имя файла, без "." и "..".
+	match file_path.components().next_back() {
+		Some(Component::Normal(name)) => {
+			// Дополнительная проверка: имя не должно содержать недопустимых символов
+			!name.to_string_lossy().contains(['/', '\\', '\0'])
+		}
+		_ => false,
+	}
+}
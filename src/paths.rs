@@ -0,0 +1,147 @@
+//! Каталоги приложения для текущего пользователя.
+//!
+//! Файлы программы не зависят от папки, из которой её запустили:
+//! - config — настройки;
+//! - data — результаты генерации (роли, карточки, QR-коды), история и списки игроков;
+//! - state — блокировка экземпляра, журнал обновления, время последней проверки;
+//! - cache — загружаемые обновления.
+//!
+//! Каждый каталог можно переопределить переменной окружения `MAFIA_<ВИД>_DIR`.
+
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const APP_DIR_NAME: &str = "MafiaGameGenerator";
+
+/// Каталоги приложения.
+pub struct AppDirs {
+	pub config: PathBuf,
+	pub data: PathBuf,
+	pub state: PathBuf,
+	pub cache: PathBuf,
+}
+
+/// Вид каталога: переменная для переопределения и стандартное расположение.
+#[derive(Clone, Copy)]
+enum DirKind {
+	Config,
+	Data,
+	State,
+	Cache,
+}
+
+impl DirKind {
+	fn override_env(self) -> &'static str {
+		match self {
+			DirKind::Config => "MAFIA_CONFIG_DIR",
+			DirKind::Data => "MAFIA_DATA_DIR",
+			DirKind::State => "MAFIA_STATE_DIR",
+			DirKind::Cache => "MAFIA_CACHE_DIR",
+		}
+	}
+
+	fn name(self) -> &'static str {
+		match self {
+			DirKind::Config => "config",
+			DirKind::Data => "data",
+			DirKind::State => "state",
+			DirKind::Cache => "cache",
+		}
+	}
+}
+
+/**
+ * Возвращает каталоги приложения. Они определяются один раз при первом обращении.
+ */
+pub fn dirs() -> &'static AppDirs {
+	static DIRS: OnceLock<AppDirs> = OnceLock::new();
+	DIRS.get_or_init(|| AppDirs {
+		config: resolve(DirKind::Config),
+		data: resolve(DirKind::Data),
+		state: resolve(DirKind::State),
+		cache: resolve(DirKind::Cache),
+	})
+}
+
+/**
+ * Создает каталог, если его еще нет, и возвращает путь к нему.
+ */
+pub fn ensure(dir: &Path) -> io::Result<&Path> {
+	std::fs::create_dir_all(dir)?;
+	Ok(dir)
+}
+
+/**
+ * Каталог для результатов генерации определенного вида (`roles`, `cards`, `qr`).
+ */
+pub fn output_dir(name: &str) -> PathBuf {
+	dirs().data.join(name)
+}
+
+/**
+ * Выводит расположение каталогов приложения (`--paths`).
+ */
+pub fn print_dirs() {
+	let dirs = dirs();
+	println!("Каталоги приложения:");
+	for (kind, path) in [
+		(DirKind::Config, &dirs.config),
+		(DirKind::Data, &dirs.data),
+		(DirKind::State, &dirs.state),
+		(DirKind::Cache, &dirs.cache),
+	] {
+		println!("  {:<7} {}  ({})", kind.name(), path.display(), kind.override_env());
+	}
+}
+
+// Переменная окружения важнее стандартного расположения. Если домашний каталог
+// определить не удалось, используется временный каталог системы.
+fn resolve(kind: DirKind) -> PathBuf {
+	if let Some(path) = env_path(kind.override_env()) {
+		return path;
+	}
+	platform_dir(kind).unwrap_or_else(|| env::temp_dir().join(APP_DIR_NAME).join(kind.name()))
+}
+
+// Путь из переменной окружения. Относительные пути игнорируются, как того требует
+// спецификация XDG: иначе каталог снова зависел бы от папки запуска.
+fn env_path(name: &str) -> Option<PathBuf> {
+	let path = PathBuf::from(env::var_os(name)?);
+	path.is_absolute().then_some(path)
+}
+
+#[cfg(windows)]
+fn platform_dir(kind: DirKind) -> Option<PathBuf> {
+	// Настройки и данные переносятся вместе с профилем, состояние и кэш — нет.
+	let base = match kind {
+		DirKind::Config | DirKind::Data => env_path("APPDATA")?,
+		DirKind::State | DirKind::Cache => env_path("LOCALAPPDATA")?,
+	};
+	Some(base.join(APP_DIR_NAME).join(kind.name()))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_dir(kind: DirKind) -> Option<PathBuf> {
+	let library = env_path("HOME")?.join("Library");
+	Some(match kind {
+		DirKind::Cache => library.join("Caches").join(APP_DIR_NAME),
+		_ => library.join("Application Support").join(APP_DIR_NAME).join(kind.name()),
+	})
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn platform_dir(kind: DirKind) -> Option<PathBuf> {
+	let (xdg_env, default) = match kind {
+		DirKind::Config => ("XDG_CONFIG_HOME", ".config"),
+		DirKind::Data => ("XDG_DATA_HOME", ".local/share"),
+		DirKind::State => ("XDG_STATE_HOME", ".local/state"),
+		DirKind::Cache => ("XDG_CACHE_HOME", ".cache"),
+	};
+	let base = match env_path(xdg_env) {
+		Some(base) => base,
+		None => env_path("HOME")?.join(default),
+	};
+	Some(base.join(APP_DIR_NAME))
+}
@@ -0,0 +1,152 @@
+use crate::error::AppError;
+use qrcode::render::unicode::Dense1x2;
+use qrcode::{Color, QrCode};
+
+/// Ширина свободной зоны вокруг QR-кода в модулях (требование стандарта).
+const QUIET_ZONE: usize = 4;
+
+/// Размер одного модуля QR-кода в пикселях для PNG.
+const PNG_MODULE_SIZE: usize = 8;
+
+/**
+ * Строит QR-код. Ошибка библиотеки сохраняется текстом, чтобы не делать её частью API.
+ */
+fn encode(data: &str) -> Result<QrCode, AppError> {
+	QrCode::new(data.as_bytes()).map_err(|e| AppError::QrCode(e.to_string()))
+}
+
+/**
+ * Возвращает QR-код в виде текста из символов Unicode для вывода в терминал.
+ * Цвета инвертированы, чтобы код читался на тёмном фоне терминала.
+ */
+pub fn render_terminal(data: &str) -> Result<String, AppError> {
+	let code = encode(data)?;
+	Ok(code
+		.render::<Dense1x2>()
+		.dark_color(Dense1x2::Light)
+		.light_color(Dense1x2::Dark)
+		.build())
+}
+
+/**
+ * Возвращает QR-код в виде SVG-изображения, пригодного для вставки в HTML-страницу.
+ */
+pub fn render_svg(data: &str) -> Result<String, AppError> {
+	let code = encode(data)?;
+	let width = code.width();
+	let size = width + 2 * QUIET_ZONE;
+	let colors = code.to_colors();
+
+	// Каждый тёмный модуль рисуется квадратом 1x1 внутри общего контура.
+	let mut path = String::new();
+	for (index, color) in colors.iter().enumerate() {
+		if *color == Color::Dark {
+			let x = index % width + QUIET_ZONE;
+			let y = index / width + QUIET_ZONE;
+			path.push_str(&format!("M{x} {y}h1v1h-1z"));
+		}
+	}
+
+	Ok(format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">\
+		<rect width=\"{size}\" height=\"{size}\" fill=\"#fff\"/><path d=\"{path}\" fill=\"#000\"/></svg>",
+		size * PNG_MODULE_SIZE,
+		size * PNG_MODULE_SIZE
+	))
+}
+
+/**
+ * Возвращает QR-код в виде PNG-изображения (чёрно-белое, 1 бит на пиксель).
+ * Кодировщик встроен и не требует внешних библиотек работы с изображениями.
+ */
+pub fn render_png(data: &str) -> Result<Vec<u8>, AppError> {
+	let code = encode(data)?;
+	let width = code.width();
+	let colors = code.to_colors();
+	let pixels = (width + 2 * QUIET_ZONE) * PNG_MODULE_SIZE;
+	let row_bytes = pixels.div_ceil(8);
+
+	// Формируем несжатые строки изображения: байт фильтра и пиксели (1 — белый, 0 — чёрный).
+	let mut raw = Vec::with_capacity((row_bytes + 1) * pixels);
+	for py in 0..pixels {
+		raw.push(0);
+		let mut row = vec![0xFFu8; row_bytes];
+		let my = py / PNG_MODULE_SIZE;
+		for px in 0..pixels {
+			let mx = px / PNG_MODULE_SIZE;
+			let dark = (QUIET_ZONE..QUIET_ZONE + width).contains(&mx)
+				&& (QUIET_ZONE..QUIET_ZONE + width).contains(&my)
+				&& colors[(my - QUIET_ZONE) * width + (mx - QUIET_ZONE)] == Color::Dark;
+			if dark {
+				row[px / 8] &= !(0x80 >> (px % 8));
+			}
+		}
+		raw.extend_from_slice(&row);
+	}
+
+	let mut header = Vec::with_capacity(13);
+	header.extend_from_slice(&(pixels as u32).to_be_bytes());
+	header.extend_from_slice(&(pixels as u32).to_be_bytes());
+	header.extend_from_slice(&[1, 0, 0, 0, 0]); // 1 бит, оттенки серого, без чересстрочности
+
+	let mut png = Vec::new();
+	png.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+	write_png_chunk(&mut png, b"IHDR", &header);
+	write_png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+	write_png_chunk(&mut png, b"IEND", &[]);
+
+	Ok(png)
+}
+
+/**
+ * Записывает блок PNG: длина, тип, данные и контрольная сумма CRC-32.
+ */
+fn write_png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+	png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+	png.extend_from_slice(kind);
+	png.extend_from_slice(data);
+	let mut crc_input = Vec::with_capacity(kind.len() + data.len());
+	crc_input.extend_from_slice(kind);
+	crc_input.extend_from_slice(data);
+	png.extend_from_slice(&crc32(&crc_input).to_be_bytes());
+}
+
+/**
+ * Упаковывает данные в поток zlib из несжатых блоков deflate.
+ * Для QR-кодов размер файла остаётся небольшим, а сжатие не требуется.
+ */
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+	let mut out = vec![0x78, 0x01];
+	let mut chunks = data.chunks(u16::MAX as usize).peekable();
+	while let Some(chunk) = chunks.next() {
+		let is_last = chunks.peek().is_none();
+		out.push(if is_last { 1 } else { 0 });
+		let len = chunk.len() as u16;
+		out.extend_from_slice(&len.to_le_bytes());
+		out.extend_from_slice(&(!len).to_le_bytes());
+		out.extend_from_slice(chunk);
+	}
+	out.extend_from_slice(&adler32(data).to_be_bytes());
+	out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+	let mut crc = 0xFFFF_FFFFu32;
+	for &byte in data {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			let mask = (crc & 1).wrapping_neg();
+			crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+		}
+	}
+	!crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+	let (mut a, mut b) = (1u32, 0u32);
+	for &byte in data {
+		a = (a + byte as u32) % 65521;
+		b = (b + a) % 65521;
+	}
+	(b << 16) | a
+}
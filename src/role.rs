@@ -1,20 +1,48 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
+use crate::error::AppError;
+use crate::game_setup::{GameConfig, GameMode};
+use std::sync::Arc;
+
+/// Наибольшее количество пользовательских ролей.
+pub const MAX_CUSTOM_ROLES: usize = 8;
+/// Наибольшее количество игроков за маленьким столом: одна мафия без Дона.
+pub const MINI_TABLE_MAX_PLAYERS: u8 = 5;
+/// С какого количества игроков мафия делится на несколько семей.
+pub const MEGA_TABLE_MIN_PLAYERS: u8 = 25;
+/// Сколько игроков за большим столом приходится на одну семью мафии.
+pub const PLAYERS_PER_MAFIA_FAMILY: u8 = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Role {
-	Civilian, // Мирный житель
-	Mafia,    // Мафия
-	Don,      // Мафия (Дон)
-	Sheriff,  // Шериф
-	Doctor,   // Доктор
-	Maniac,   // Маньяк
+	Civilian,                // Мирный житель
+	Mafia,                   // Мафия
+	Don,                     // Мафия (Дон)
+	Sheriff,                 // Шериф
+	Doctor,                  // Доктор
+	Maniac,                  // Маньяк
+	Custom(Arc<CustomRole>), // Пользовательская роль из настроек игры (`GameConfig::with_custom_roles`)
+}
+
+/// Пользовательская роль из файла настроек. Занимает место одного из мирных жителей,
+/// а роль стороны мафии — место рядового мафиози, чтобы мафия оставалась в меньшинстве.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomRole {
+	pub name: String,
+	pub description: String,
+	pub faction: Faction,
+	pub icon: String,
+	/// Сколько таких ролей в игре.
+	pub count: u8,
+	/// С какого количества игроков роль появляется.
+	pub min_players: u8,
 }
 
 // Здесь мы определяем методы, которые будут доступны для любого значения типа Role.
 impl Role {
 	/// &self - означает, что метод заимствует экземпляр, но не изменяет его.
-	/// &'static str - возвращает строковый срез, который "вшит" в программу и существует всё время её жизни.
-	/// Это очень эффективно по памяти и скорости.
-	pub fn get_name(&self) -> &'static str {
+	/// Названия встроенных ролей "вшиты" в программу, а пользовательских — берутся из самой роли,
+	/// поэтому возвращаемый срез живёт не дольше `self`.
+	pub fn get_name(&self) -> &str {
 		match self {
 			Role::Civilian => "Мирный житель",
 			Role::Mafia => "Мафия",
@@ -22,11 +50,12 @@ impl Role {
 			Role::Sheriff => "Шериф",
 			Role::Doctor => "Доктор",
 			Role::Maniac => "Маньяк",
+			Role::Custom(role) => &role.name,
 		}
 	}
 
 	/// Возвращает описание роли и её целей в игре.
-	pub fn get_description(&self) -> &'static str {
+	pub fn get_description(&self) -> &str {
 		match self {
 			Role::Civilian => "Ваша цель - выжить и вычислить всех членов мафии. Вы побеждаете, когда в городе не остается мафии.",
 			Role::Mafia => "Вы - член мафии. Ночью вы просыпаетесь вместе с другими мафиози и выбираете жертву. Ваша цель - добиться численного равенства с мирными жителями.",
@@ -34,45 +63,179 @@ impl Role {
 			Role::Sheriff => "Вы - Шериф. Ночью вы можете проверить одного из игроков, чтобы узнать, принадлежит ли он к мафии. Ваша цель - помочь мирным жителям найти и казнить мафию.",
 			Role::Doctor => "Вы - Доктор. Ночью вы можете 'вылечить' одного игрока, спасая его от выстрела мафии. Вы не можете лечить одного и того же игрока две ночи подряд (по классическим правилам).",
 			Role::Maniac => "Вы играете сами за себя. Каждую ночь вы просыпаетесь и выбираете, кого убить. Ваша цель — остаться последним выжившим в городе.",
+			Role::Custom(role) => &role.description,
+		}
+	}
+
+	/// Возвращает сторону, за которую играет роль.
+	pub fn get_faction(&self) -> Faction {
+		match self {
+			Role::Civilian | Role::Sheriff | Role::Doctor => Faction::Town,
+			Role::Mafia | Role::Don => Faction::Mafia,
+			Role::Maniac => Faction::Neutral,
+			Role::Custom(role) => role.faction,
+		}
+	}
+
+	/// Знает ли роль своих союзников в начале игры.
+	/// По классическим правилам мафия (включая Дона) знакомится в первую ночь.
+	/// Пользовательские роли мафии знакомятся с ней так же.
+	pub fn knows_teammates(&self) -> bool {
+		match self {
+			Role::Mafia | Role::Don => true,
+			Role::Custom(_) => self.get_faction() == Faction::Mafia,
+			_ => false,
+		}
+	}
+
+	/// Возвращает значок роли для карточек.
+	pub fn get_icon(&self) -> &str {
+		match self {
+			Role::Civilian => "🏠",
+			Role::Mafia => "🔫",
+			Role::Don => "🎩",
+			Role::Sheriff => "⭐",
+			Role::Doctor => "💉",
+			Role::Maniac => "🔪",
+			Role::Custom(role) => &role.icon,
+		}
+	}
+
+	/// Числовой код роли (используется в C ABI).
+	/// У пользовательских ролей кода нет: они существуют только в настройках конкретной игры.
+	pub fn code(&self) -> Option<u8> {
+		match self {
+			Role::Civilian => Some(0),
+			Role::Mafia => Some(1),
+			Role::Don => Some(2),
+			Role::Sheriff => Some(3),
+			Role::Doctor => Some(4),
+			Role::Maniac => Some(5),
+			Role::Custom(_) => None,
 		}
 	}
 
 	/// Возвращает количество каждого типа роли для заданного количества игроков и режима игры.
 	/// Это эффективный с точки зрения памяти способ вычисления ролей без создания векторов.
-	pub fn get_role_counts(player_count: u8, game_mode: crate::game_setup::GameMode) -> RoleCounts {
+	/// Для маленьких (до `MINI_TABLE_MAX_PLAYERS`) и больших (от `MEGA_TABLE_MIN_PLAYERS`) столов
+	/// используются отдельные раскладки; если ролей оказывается больше, чем игроков, возвращается ошибка.
+	pub fn get_role_counts(config: &GameConfig) -> Result<RoleCounts, AppError> {
+		let player_count = config.player_count;
+
 		// Рассчитываем количество ключевых ролей по формулам
 		// Мафия составляет примерно треть от всех игроков.
-		let num_mafia_total = (player_count as f32 / 3.0).floor() as u8;
-		// Если мафиози больше одного, один из них становится Доном.
-		let num_don = if num_mafia_total > 1 { 1 } else { 0 };
-		let num_mafia = num_mafia_total - num_don;
-		// В игре всегда есть один Шериф и один Доктор (по нашей логике).
-		let num_sheriff = 1;
-		let num_doctor = 1;
-
-		// Добавляем Маньяка только в расширенном режиме и если игроков достаточно (например, 8+)
-		let num_maniac = match game_mode {
-			crate::game_setup::GameMode::Classic => 0,
-			crate::game_setup::GameMode::Extended if player_count >= 8 => 1,
-			_ => 0, // В остальных случаях (например, в расширенном режиме, но < 8 игроков) маньяка нет
+		let num_mafia_total = player_count / 3;
+		// Количество мафиозных семей: за большим столом мафия делится на несколько кланов.
+		let families = mafia_families(player_count);
+		// Если мафиози больше одного, один из них становится Доном; у каждой семьи свой Дон.
+		let num_don = if num_mafia_total > 1 { families } else { 0 };
+		// В игре по одному Шерифу и Доктору на каждую семью мафии.
+		// За маленьким столом Доктор появляется только с пятью игроками.
+		let num_sheriff = families;
+		let num_doctor = if player_count >= MINI_TABLE_MAX_PLAYERS { families } else { 0 };
+
+		// Добавляем Маньяка только в расширенном режиме и если игроков достаточно (по умолчанию 8+)
+		let num_maniac = match config.game_mode {
+			GameMode::Classic => 0,
+			GameMode::Extended if player_count >= config.maniac_min_players => 1,
+			_ => 0, // В остальных случаях (например, в расширенном режиме, но мало игроков) маньяка нет
 		};
 
 		// Считаем, сколько осталось мирных жителей
-		let active_roles_count = num_mafia_total + num_sheriff + num_doctor + num_maniac;
-		let num_civilians = player_count - active_roles_count;
+		let layout_error = || AppError::InvalidRoleLayout { players: player_count };
+		let mut num_mafia = num_mafia_total.checked_sub(num_don).ok_or_else(layout_error)?;
+		let active_roles_count = [num_sheriff, num_doctor, num_maniac]
+			.into_iter()
+			.try_fold(num_mafia_total, u8::checked_add)
+			.ok_or_else(layout_error)?;
+		let mut num_civilians = player_count.checked_sub(active_roles_count).ok_or_else(layout_error)?;
 
-		RoleCounts {
+		// Пользовательские роли занимают места мирных жителей, пока они есть, а роли стороны мафии —
+		// места рядовых мафиози: так доля мафии за столом не меняется.
+		// Роли, которые за таким столом ещё не появляются, остаются в списке с нулевым количеством.
+		let mut custom = Vec::with_capacity(config.custom_roles.len());
+		for role in &config.custom_roles {
+			let replaced = match role.faction {
+				Faction::Mafia => &mut num_mafia,
+				Faction::Town | Faction::Neutral => &mut num_civilians,
+			};
+			let count = if player_count >= role.min_players { role.count.min(*replaced) } else { 0 };
+			*replaced -= count;
+			custom.push((Arc::clone(role), count));
+		}
+
+		Ok(RoleCounts {
 			civilians: num_civilians,
 			mafia: num_mafia,
 			don: num_don,
 			sheriff: num_sheriff,
 			doctor: num_doctor,
 			maniac: num_maniac,
+			custom,
+		})
+	}
+}
+
+/**
+ * Возвращает количество мафиозных семей для стола из `player_count` игроков:
+ * одна семья до `MEGA_TABLE_MIN_PLAYERS`, дальше по семье на каждые `PLAYERS_PER_MAFIA_FAMILY` игроков.
+ */
+pub fn mafia_families(player_count: u8) -> u8 {
+	if player_count < MEGA_TABLE_MIN_PLAYERS {
+		1
+	} else {
+		(player_count / PLAYERS_PER_MAFIA_FAMILY).max(2)
+	}
+}
+
+/// Восстанавливает роль по её числовому коду (используется в C ABI).
+impl TryFrom<u8> for Role {
+	type Error = crate::error::AppError;
+
+	fn try_from(code: u8) -> Result<Self, Self::Error> {
+		match code {
+			0 => Ok(Role::Civilian),
+			1 => Ok(Role::Mafia),
+			2 => Ok(Role::Don),
+			3 => Ok(Role::Sheriff),
+			4 => Ok(Role::Doctor),
+			5 => Ok(Role::Maniac),
+			_ => Err(crate::error::AppError::InvalidRole(code)),
 		}
 	}
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Сторона (команда), за которую играет роль.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Faction {
+	Town,    // Мирные жители
+	Mafia,   // Мафия
+	Neutral, // Играет сам за себя
+}
+
+impl Faction {
+	/// Возвращает название стороны.
+	pub fn get_name(&self) -> &'static str {
+		match self {
+			Faction::Town => "Мирные жители",
+			Faction::Mafia => "Мафия",
+			Faction::Neutral => "Одиночка",
+		}
+	}
+
+	/// Возвращает цвет стороны для карточек (в формате CSS).
+	pub fn get_color(&self) -> &'static str {
+		match self {
+			Faction::Town => "#2e7d32",
+			Faction::Mafia => "#b71c1c",
+			Faction::Neutral => "#6a1b9a",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct RoleCounts {
 	pub civilians: u8,
 	pub mafia: u8,
@@ -80,17 +243,58 @@ pub struct RoleCounts {
 	pub sheriff: u8,
 	pub doctor: u8,
 	pub maniac: u8,
+	/// Пользовательские роли игры и их количество (в порядке `GameConfig::custom_roles`).
+	pub custom: Vec<(Arc<CustomRole>, u8)>,
 }
 
 impl RoleCounts {
 	/// Возвращает общее количество ролей.
-	pub fn total(&self) -> u8 {
-		self.civilians + self.mafia + self.don + self.sheriff + self.doctor + self.maniac
+	/// Считается в `usize`, поэтому не переполняется даже при ручной правке состава.
+	pub fn total(&self) -> usize {
+		[self.civilians, self.mafia, self.don, self.sheriff, self.doctor, self.maniac]
+			.iter()
+			.chain(self.custom.iter().map(|(_, count)| count))
+			.map(|&count| count as usize)
+			.sum()
+	}
+
+	/// Возвращает роли и их количество в порядке показа ведущему:
+	/// все встроенные роли и пользовательские роли игры, в том числе отсутствующие в игре.
+	pub fn entries(&self) -> Vec<(Role, u8)> {
+		let mut entries = vec![
+			(Role::Civilian, self.civilians),
+			(Role::Mafia, self.mafia),
+			(Role::Don, self.don),
+			(Role::Sheriff, self.sheriff),
+			(Role::Doctor, self.doctor),
+			(Role::Maniac, self.maniac),
+		];
+		for (role, count) in &self.custom {
+			entries.push((Role::Custom(Arc::clone(role)), *count));
+		}
+		entries
+	}
+
+	/// Задает количество определенной роли.
+	pub fn set_count(&mut self, role: Role, count: u8) {
+		let slot = match role {
+			Role::Civilian => &mut self.civilians,
+			Role::Mafia => &mut self.mafia,
+			Role::Don => &mut self.don,
+			Role::Sheriff => &mut self.sheriff,
+			Role::Doctor => &mut self.doctor,
+			Role::Maniac => &mut self.maniac,
+			Role::Custom(role) => match self.custom.iter_mut().find(|(custom, _)| *custom == role) {
+				Some((_, slot)) => slot,
+				None => return,
+			},
+		};
+		*slot = count;
 	}
 
 	/// Создает вектор ролей из подсчетов.
-	pub fn to_vec(self) -> Vec<Role> {
-		let mut roles = Vec::with_capacity(self.total() as usize);
+	pub fn to_vec(&self) -> Vec<Role> {
+		let mut roles = Vec::with_capacity(self.total());
 
 		// Добавляем рассчитанное количество каждой роли
 		for _ in 0..self.mafia { roles.push(Role::Mafia); }
@@ -99,7 +303,55 @@ impl RoleCounts {
 		for _ in 0..self.doctor { roles.push(Role::Doctor); }
 		for _ in 0..self.civilians { roles.push(Role::Civilian); }
 		for _ in 0..self.maniac { roles.push(Role::Maniac); }
+		for (role, count) in &self.custom {
+			for _ in 0..*count { roles.push(Role::Custom(Arc::clone(role))); }
+		}
 
 		roles
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn custom_role(faction: Faction) -> CustomRole {
+		CustomRole {
+			name: "Роль".to_string(),
+			description: String::new(),
+			faction,
+			icon: "🎭".to_string(),
+			count: 1,
+			min_players: 0,
+		}
+	}
+
+	fn mafia_total(counts: &RoleCounts) -> usize {
+		counts
+			.entries()
+			.iter()
+			.filter(|(role, _)| role.get_faction() == Faction::Mafia)
+			.map(|(_, count)| *count as usize)
+			.sum()
+	}
+
+	#[test]
+	fn mafia_custom_role_replaces_mafia() {
+		let config = GameConfig::new(6, GameMode::Classic).with_custom_roles(vec![custom_role(Faction::Mafia)]).unwrap();
+		let counts = Role::get_role_counts(&config).unwrap();
+		assert_eq!(counts.total(), 6);
+		assert_eq!(counts.mafia, 0);
+		assert_eq!(counts.custom[0].1, 1);
+		assert_eq!(mafia_total(&counts), 2);
+	}
+
+	#[test]
+	fn town_custom_role_replaces_civilian() {
+		let config = GameConfig::new(6, GameMode::Classic).with_custom_roles(vec![custom_role(Faction::Town)]).unwrap();
+		let counts = Role::get_role_counts(&config).unwrap();
+		assert_eq!(counts.total(), 6);
+		assert_eq!(counts.civilians, 1);
+		assert_eq!(counts.custom[0].1, 1);
+		assert_eq!(mafia_total(&counts), 2);
+	}
+}
@@ -0,0 +1,353 @@
+use mafia_game_generator::cards::escape_html;
+use mafia_game_generator::error::AppError;
+use mafia_game_generator::game_setup::Assignment;
+use mafia_game_generator::qr;
+use mafia_game_generator::role::Role;
+use rand::RngCore;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream, UdpSocket};
+use std::path::Path;
+use std::time::Duration;
+
+/// Порт локального сервера по умолчанию.
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// Максимальный размер заголовков запроса, который мы готовы прочитать.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Карточка игрока на сервере: персональная ссылка и отметка о просмотре.
+struct PlayerTicket {
+	name: String,
+	role: Role,
+	/// Известные игроку союзники (пустая строка, если их нет).
+	teammates: String,
+	token: String,
+	qr_svg: String,
+	viewed: bool,
+}
+
+/// Ответ сервера: код статуса и HTML-страница.
+struct Response {
+	status: &'static str,
+	body: String,
+}
+
+/**
+ * Запускает локальный HTTP-сервер, на котором каждый игрок один раз может посмотреть свою роль
+ * по персональной ссылке. Сервер работает без доступа в интернет и завершается,
+ * когда все игроки посмотрели свои роли. QR-коды ссылок сохраняются в `qr_dir`.
+ */
+pub fn run_server(assignment: &Assignment, port: u16, qr_dir: &Path) -> Result<(), AppError> {
+	let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+	let address = local_lan_address();
+
+	let mut tickets = Vec::with_capacity(assignment.players.len());
+	for (index, (name, role)) in assignment.players.iter().enumerate() {
+		let token = generate_token();
+		let url = format!("http://{}:{}/p/{}", address, port, token);
+		tickets.push(PlayerTicket {
+			name: name.clone(),
+			role: role.clone(),
+			teammates: assignment.describe_allies(index),
+			qr_svg: qr::render_svg(&url)?,
+			token,
+			viewed: false,
+		});
+	}
+	let host_token = generate_token();
+	// Роли открываются по отдельной ссылке: страницу с QR-кодами видят игроки.
+	let roles_token = generate_token();
+
+	println!("\nСервер запущен. Игроки сканируют свой QR-код или открывают персональную ссылку:");
+	let mut qr_files = Vec::with_capacity(tickets.len());
+	for ticket in &tickets {
+		let url = format!("http://{}:{}/p/{}", address, port, ticket.token);
+		println!("\n  {} — {}", ticket.name, url);
+		println!("{}", qr::render_terminal(&url)?);
+		qr_files.push((ticket.name.clone(), url));
+	}
+
+	// QR-коды также сохраняются в файлы, чтобы их можно было показать с другого экрана.
+	match mafia_game_generator::output::write_qr_files(qr_dir, &qr_files) {
+		Ok(()) => println!("QR-коды сохранены в папке '{}' (PNG и SVG).", qr_dir.display()),
+		Err(e) => eprintln!("Предупреждение: Не удалось сохранить QR-коды: {}", e),
+	}
+
+	println!("\nСтраница ведущего с QR-кодами всех игроков: http://{}:{}/host/{}", address, port, host_token);
+	println!("Роли всех игроков (не открывайте на общем экране): http://{}:{}/roles/{}", address, port, roles_token);
+	println!("Устройства игроков должны быть подключены к той же сети. Для остановки нажмите Ctrl+C.\n");
+
+	for stream in listener.incoming() {
+		let mut stream = match stream {
+			Ok(stream) => stream,
+			Err(e) => {
+				eprintln!("Предупреждение: Не удалось принять соединение: {}", e);
+				continue;
+			}
+		};
+
+		// Ошибка одного клиента не должна останавливать сервер.
+		if let Err(e) = handle_connection(&mut stream, &mut tickets, &host_token, &roles_token) {
+			eprintln!("Предупреждение: Ошибка при обработке запроса: {}", e);
+		}
+
+		if tickets.iter().all(|ticket| ticket.viewed) {
+			println!("\nВсе игроки посмотрели свои роли. Сервер остановлен.");
+			break;
+		}
+	}
+
+	Ok(())
+}
+
+/**
+ * Читает один запрос, формирует ответ и отправляет его клиенту.
+ */
+fn handle_connection(
+	stream: &mut TcpStream,
+	tickets: &mut [PlayerTicket],
+	host_token: &str,
+	roles_token: &str,
+) -> Result<(), AppError> {
+	stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+	stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+
+	let (method, path) = match read_request(stream)? {
+		Some(request) => request,
+		None => return Ok(()),
+	};
+
+	let response = route(&method, &path, tickets, host_token, roles_token);
+
+	let raw = format!(
+		"HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nReferrer-Policy: no-referrer\r\nConnection: close\r\n\r\n{}",
+		response.status,
+		response.body.len(),
+		response.body
+	);
+	stream.write_all(raw.as_bytes())?;
+	stream.flush()?;
+
+	Ok(())
+}
+
+/**
+ * Читает заголовки запроса (и тело, если оно есть) и возвращает метод и путь.
+ */
+fn read_request(stream: &mut TcpStream) -> Result<Option<(String, String)>, AppError> {
+	let mut buffer = Vec::with_capacity(1024);
+	let mut chunk = [0u8; 1024];
+
+	// Читаем, пока не встретим конец заголовков.
+	let header_end = loop {
+		if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+			break pos + 4;
+		}
+		if buffer.len() > MAX_REQUEST_SIZE {
+			return Ok(None);
+		}
+		let read = stream.read(&mut chunk)?;
+		if read == 0 {
+			return Ok(None);
+		}
+		buffer.extend_from_slice(&chunk[..read]);
+	};
+
+	let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+	let mut lines = head.lines();
+	let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+	let method = request_line.next().unwrap_or_default().to_string();
+	let path = request_line.next().unwrap_or_default().to_string();
+
+	// Дочитываем тело запроса, чтобы клиент не получил обрыв соединения вместо ответа.
+	let content_length = lines
+		.filter_map(|line| line.split_once(':'))
+		.find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+		.and_then(|(_, value)| value.trim().parse::<usize>().ok())
+		.unwrap_or(0)
+		.min(MAX_REQUEST_SIZE);
+	let mut remaining = content_length.saturating_sub(buffer.len() - header_end);
+	while remaining > 0 {
+		let read = stream.read(&mut chunk[..remaining.min(1024)])?;
+		if read == 0 {
+			break;
+		}
+		remaining -= read;
+	}
+
+	Ok(Some((method, path)))
+}
+
+/**
+ * Выбирает страницу по методу и пути запроса.
+ */
+fn route(method: &str, path: &str, tickets: &mut [PlayerTicket], host_token: &str, roles_token: &str) -> Response {
+	if let Some(token) = path.strip_prefix("/host/") {
+		if token == host_token {
+			return Response { status: "200 OK", body: host_page(tickets) };
+		}
+		return not_found();
+	}
+	if let Some(token) = path.strip_prefix("/roles/") {
+		if token == roles_token {
+			return Response { status: "200 OK", body: roles_page(tickets) };
+		}
+		return not_found();
+	}
+
+	let token = match path.strip_prefix("/p/") {
+		Some(token) => token,
+		None => return not_found(),
+	};
+	let ticket = match tickets.iter_mut().find(|ticket| ticket.token == token) {
+		Some(ticket) => ticket,
+		None => return not_found(),
+	};
+
+	if ticket.viewed {
+		let body = page(
+			"Роль уже показана",
+			&format!("<h1>{}</h1><p>Эта роль уже была показана. Если это были не вы, сообщите ведущему.</p>", escape_html(&ticket.name)),
+		);
+		return Response { status: "410 Gone", body };
+	}
+
+	match method {
+		// Роль показывается только после нажатия кнопки, чтобы предпросмотр ссылки
+		// в мессенджере или браузере не "сжёг" её раньше времени.
+		"GET" => {
+			let body = page(
+				"Ваша роль",
+				&format!(
+					"<h1>{}</h1><p>Убедитесь, что никто не смотрит на экран. Роль будет показана только один раз.</p>\
+					<form method=\"post\"><button type=\"submit\">Показать роль</button></form>",
+					escape_html(&ticket.name)
+				),
+			);
+			Response { status: "200 OK", body }
+		}
+		"POST" => {
+			ticket.viewed = true;
+			println!("  ✓ {} посмотрел(а) свою роль.", ticket.name);
+			let team = if ticket.teammates.is_empty() {
+				String::new()
+			} else {
+				format!("<p><strong>Ваша команда:</strong> {}</p>", escape_html(&ticket.teammates))
+			};
+			let body = page(
+				"Ваша роль",
+				&format!(
+					"<h1>{}</h1><h2>{}</h2><p>{}</p>{}<p class=\"note\">Запомните роль и закройте страницу. Повторно она показана не будет.</p>",
+					escape_html(&ticket.name),
+					escape_html(ticket.role.get_name()),
+					escape_html(ticket.role.get_description()),
+					team
+				),
+			);
+			Response { status: "200 OK", body }
+		}
+		_ => Response { status: "405 Method Not Allowed", body: page("Ошибка", "<p>Метод не поддерживается.</p>") },
+	}
+}
+
+/**
+ * Формирует страницу ведущего со списком игроков и отметками о просмотре ролей.
+ */
+fn host_page(tickets: &[PlayerTicket]) -> String {
+	let viewed_count = tickets.iter().filter(|ticket| ticket.viewed).count();
+	let rows: String = tickets
+		.iter()
+		.map(|ticket| {
+			// QR-код показывается, только пока игрок не посмотрел роль.
+			let (status, qr) = if ticket.viewed {
+				("✓ посмотрел(а)", "")
+			} else {
+				("— ещё нет", ticket.qr_svg.as_str())
+			};
+			format!("<tr><td>{}</td><td>{}</td><td class=\"qr\">{}</td></tr>", escape_html(&ticket.name), status, qr)
+		})
+		.collect();
+
+	page(
+		"Ведущий",
+		&format!(
+			"<meta http-equiv=\"refresh\" content=\"5\"><h1>Ведущий</h1><p>Посмотрели роль: {} из {}</p>\
+			<table><tr><th>Игрок</th><th>Статус</th><th>QR-код</th></tr>{}</table>",
+			viewed_count,
+			tickets.len(),
+			rows
+		),
+	)
+}
+
+/**
+ * Формирует страницу с ролями всех игроков. Она открывается по отдельной ссылке,
+ * которая выводится только в терминале ведущего: игроки сканируют QR-коды со страницы ведущего.
+ */
+fn roles_page(tickets: &[PlayerTicket]) -> String {
+	let roles: String = tickets
+		.iter()
+		.map(|ticket| {
+			format!(
+				"<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+				escape_html(&ticket.name),
+				escape_html(ticket.role.get_name()),
+				escape_html(&ticket.teammates)
+			)
+		})
+		.collect();
+
+	page(
+		"Роли игроков",
+		&format!(
+			"<h1>Роли игроков</h1><table><tr><th>Игрок</th><th>Роль</th><th>Знает союзников</th></tr>{}</table>",
+			roles
+		),
+	)
+}
+
+fn not_found() -> Response {
+	Response { status: "404 Not Found", body: page("Не найдено", "<p>Ссылка недействительна.</p>") }
+}
+
+/**
+ * Оборачивает содержимое в HTML-страницу с минимальными стилями для телефона.
+ */
+fn page(title: &str, content: &str) -> String {
+	format!(
+		"<!DOCTYPE html><html lang=\"ru\"><head><meta charset=\"utf-8\">\
+		<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+		<meta name=\"robots\" content=\"noindex\"><title>{}</title>\
+		<style>body{{font-family:sans-serif;max-width:40em;margin:2em auto;padding:0 1em;}}\
+		button{{font-size:1.2em;padding:.6em 1.2em;}}table{{border-collapse:collapse;}}\
+		td,th{{border:1px solid #999;padding:.3em .6em;text-align:left;}}.note{{color:#666;}}.qr svg{{width:10em;height:10em;}}</style>\
+		</head><body>{}</body></html>",
+		escape_html(title),
+		content
+	)
+}
+
+/**
+ * Генерирует случайный токен из 128 бит в шестнадцатеричном виде.
+ */
+fn generate_token() -> String {
+	let mut bytes = [0u8; 16];
+	rand::rng().fill_bytes(&mut bytes);
+	bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/**
+ * Определяет адрес компьютера в локальной сети. UDP-сокет только выбирает маршрут,
+ * пакеты при этом не отправляются, поэтому функция работает и без интернета.
+ */
+fn local_lan_address() -> IpAddr {
+	["192.168.0.1:9", "10.0.0.1:9", "172.16.0.1:9"]
+		.iter()
+		.find_map(|target| {
+			let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+			socket.connect(target).ok()?;
+			let ip = socket.local_addr().ok()?.ip();
+			(!ip.is_unspecified()).then_some(ip)
+		})
+		.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
@@ -0,0 +1,282 @@
+use mafia_game_generator::crypto;
+use mafia_game_generator::error::AppError;
+use mafia_game_generator::game_setup::{get_roles_for_players, shuffle_roles, Assignment, GameMode, OutputFormat};
+use mafia_game_generator::output::OutputWarning;
+use mafia_game_generator::template::RoleTemplate;
+use crate::config::Config;
+use crate::io_handler::{reveal_pins_in_turn, reveal_roles_in_turn, wait_for_enter};
+
+/**
+ * Отделяет PIN-коды от имён игроков, переданных в виде `Имя:PIN`.
+ * Для игроков без PIN-кода возвращается `None`.
+ */
+fn split_player_pins(player_names: Vec<String>) -> Result<(Vec<String>, Vec<Option<String>>), AppError> {
+	let mut names = Vec::with_capacity(player_names.len());
+	let mut pins = Vec::with_capacity(player_names.len());
+
+	for entry in player_names {
+		match entry.split_once(':') {
+			Some((name, pin)) => {
+				crypto::validate_pin(pin)?;
+				names.push(name.to_string());
+				pins.push(Some(pin.to_string()));
+			}
+			None => {
+				names.push(entry);
+				pins.push(None);
+			}
+		}
+	}
+
+	Ok((names, pins))
+}
+
+/**
+ * Сохраняет роли игроков в выбранном формате в каталог данных пользователя или в каталог из настроек.
+ * Для зашифрованных файлов недостающие PIN-коды генерируются и показываются каждому игроку лично.
+ */
+fn save_assignment(
+	settings: &Config,
+	players_with_roles: &Assignment,
+	output_format: OutputFormat,
+	supplied_pins: Vec<Option<String>>,
+	template: &RoleTemplate
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	use mafia_game_generator::output::{write_card_files, write_encrypted_role_files, write_role_files};
+
+	let roles_dir = settings.output_dir("roles")?;
+	match output_format {
+		OutputFormat::Text => match write_role_files(&roles_dir, players_with_roles, template) {
+			Ok(warnings) => {
+				print_warnings(&warnings);
+				println!("\nУспех! Роли сгенерированы и сохранены в папке '{}'.", roles_dir.display());
+				println!("Для каждого игрока создан персональный файл. Количество игроков: {}", players_with_roles.players.len());
+			}
+			Err(e) => {
+				return Err(format!("Ошибка при записи файлов: {}", e).into());
+			}
+		},
+		OutputFormat::Encrypted => {
+			// Недостающие PIN-коды генерируем случайно
+			let generated: Vec<Option<String>> = supplied_pins
+				.iter()
+				.map(|pin| if pin.is_none() { Some(crypto::generate_pin()) } else { None })
+				.collect();
+			let pins: Vec<String> = supplied_pins
+				.into_iter()
+				.zip(&generated)
+				.map(|(supplied, generated)| supplied.or_else(|| generated.clone()).unwrap_or_default())
+				.collect();
+
+			match write_encrypted_role_files(&roles_dir, players_with_roles, &pins, template) {
+				Ok(warnings) => print_warnings(&warnings),
+				Err(e) => {
+					return Err(format!("Ошибка при записи файлов: {}", e).into());
+				}
+			}
+
+			println!("\nУспех! Роли зашифрованы и сохранены в папке '{}'.", roles_dir.display());
+			println!("Для каждого игрока создан персональный файл. Количество игроков: {}", players_with_roles.players.len());
+
+			println!("\nДля просмотра роли: MafiaGameGenerator --reveal <файл> [PIN]");
+
+			// PIN-коды нигде не сохраняются: каждый игрок видит свой один раз, передавая компьютер по кругу
+			if generated.iter().any(Option::is_some) {
+				wait_for_enter("\nНажмите Enter, чтобы показать игрокам их PIN-коды...")?;
+				reveal_pins_in_turn(players_with_roles, &generated)?;
+			}
+		}
+		OutputFormat::Cards(options) => match write_card_files(&settings.output_dir("cards")?, players_with_roles, &options) {
+			Ok(files) => {
+				println!("\nУспех! Карточки ролей подготовлены для печати на листах A4:");
+				for file in files {
+					println!("  {}", file.display());
+				}
+			}
+			Err(e) => {
+				return Err(format!("Ошибка при записи файлов: {}", e).into());
+			}
+		},
+	}
+
+	Ok(())
+}
+
+/**
+ * Выводит предупреждения, возникшие при записи файлов ролей.
+ */
+fn print_warnings(warnings: &[OutputWarning]) {
+	for warning in warnings {
+		eprintln!("Предупреждение: {warning}");
+	}
+}
+
+pub fn run_headless_mode(
+	settings: &Config,
+	player_count: u8,
+	game_mode: GameMode,
+	player_names: Vec<String>,
+	output_format: Option<OutputFormat>,
+	template_path: Option<String>
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	// Создаем конфигурацию с ограничениями из файла настроек
+	let config = settings.game_config(player_count, game_mode)?;
+
+	// Валидируем количество
+	let mut roles = match get_roles_for_players(&config) {
+		Ok(role_set) => role_set,
+		Err(e) => {
+			return Err(format!("Ошибка конфигурации: {}", e).into());
+		}
+	};
+
+	// Загружаем шаблон файла роли: из параметра --template, из файла настроек или встроенный
+	let template = match settings.role_template(template_path.as_deref()) {
+		Ok(template) => template,
+		Err(e) => {
+			return Err(format!("Ошибка загрузки шаблона: {}", e).into());
+		}
+	};
+
+	// Формат вывода, не указанный в командной строке, берется из настроек
+	let output_format = match output_format {
+		Some(format) => format,
+		None => settings.output_format()?,
+	};
+
+	// Для зашифрованных файлов имена могут содержать заданные PIN-коды
+	let (player_names, supplied_pins) = match output_format {
+		OutputFormat::Text | OutputFormat::Cards(_) => (player_names, Vec::new()),
+		OutputFormat::Encrypted => match split_player_pins(player_names) {
+			Ok(split) => split,
+			Err(e) => {
+				return Err(format!("Ошибка в списке игроков: {}", e).into());
+			}
+		},
+	};
+
+	println!("\nГенерирую {} ролей для ваших игроков...", roles.len());
+
+	// Перемешиваем роли
+	shuffle_roles(&mut roles);
+
+	// Соединяем имена с ролями и определяем, кто из игроков знает друг друга
+	let players_with_roles = Assignment::new(player_names, roles);
+
+	save_assignment(settings, &players_with_roles, output_format, supplied_pins, &template)?;
+
+	// Добавляем небольшую задержку, чтобы убедиться, что файлы записались
+	std::thread::sleep(std::time::Duration::from_millis(100));
+
+	Ok(())
+}
+
+/**
+ * Генерирует роли и показывает их по очереди на одном экране, который передают от игрока к игроку.
+ * Файлы сохраняются только по запросу (`--save`), в формате из настроек.
+ */
+pub fn run_pass_mode(
+	settings: &Config,
+	player_count: u8,
+	game_mode: GameMode,
+	player_names: Vec<String>,
+	save_files: bool
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	// Создаем конфигурацию с ограничениями из файла настроек
+	let config = settings.game_config(player_count, game_mode)?;
+
+	// Валидируем количество
+	let mut roles = match get_roles_for_players(&config) {
+		Ok(role_set) => role_set,
+		Err(e) => {
+			return Err(format!("Ошибка конфигурации: {}", e).into());
+		}
+	};
+
+	// Перемешиваем роли
+	shuffle_roles(&mut roles);
+
+	// Соединяем имена с ролями и определяем, кто из игроков знает друг друга
+	let players_with_roles = Assignment::new(player_names, roles);
+
+	if save_files {
+		let supplied_pins = vec![None; players_with_roles.players.len()];
+		save_assignment(settings, &players_with_roles, settings.output_format()?, supplied_pins, &settings.role_template(None)?)?;
+		// Сообщение о сохранении видит ведущий до того, как экран будет очищен
+		wait_for_enter("\nНажмите Enter, чтобы начать показ ролей...")?;
+	}
+
+	reveal_roles_in_turn(&players_with_roles)?;
+	Ok(())
+}
+
+#[cfg(feature = "serve")]
+pub fn run_serve_mode(
+	settings: &Config,
+	player_count: u8,
+	game_mode: GameMode,
+	player_names: Vec<String>,
+	port: u16
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	use crate::server::run_server;
+
+	// Создаем конфигурацию с ограничениями из файла настроек
+	let config = settings.game_config(player_count, game_mode)?;
+
+	// Валидируем количество
+	let mut roles = match get_roles_for_players(&config) {
+		Ok(role_set) => role_set,
+		Err(e) => {
+			return Err(format!("Ошибка конфигурации: {}", e).into());
+		}
+	};
+
+	println!("\nГенерирую {} ролей для ваших игроков...", roles.len());
+
+	// Перемешиваем роли
+	shuffle_roles(&mut roles);
+
+	// Соединяем имена с ролями и определяем, кто из игроков знает друг друга
+	let players_with_roles = Assignment::new(player_names, roles);
+
+	// Роли не записываются на диск: каждый игрок смотрит свою роль по персональной ссылке.
+	if let Err(e) = run_server(&players_with_roles, port, &settings.output_dir("qr")?) {
+		return Err(format!("Ошибка локального сервера: {}", e).into());
+	}
+
+	Ok(())
+}
+
+pub fn run_interactive_mode(settings: &Config) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	// Шаблон файла роли задается в файле настроек
+	let template = settings.role_template(None)?;
+
+	// Мастер настройки: режим, состав ролей, игроки и формат вывода
+	let setup = crate::wizard::run(settings)?;
+
+	let mut roles = setup.role_counts.to_vec();
+	println!("\nГенерирую {} ролей для ваших игроков...", roles.len());
+
+	// Перемешиваем роли
+	shuffle_roles(&mut roles);
+
+	// Соединяем имена с ролями и определяем, кто из игроков знает друг друга
+	let players_with_roles = Assignment::new(setup.player_names.clone(), roles);
+
+	match setup.output_format {
+		Some(output_format) => {
+			// PIN-коды в интерактивном режиме всегда генерируются
+			let supplied_pins = vec![None; setup.player_names.len()];
+			save_assignment(settings, &players_with_roles, output_format, supplied_pins, &template)?;
+		}
+		// Роли показываются на экране и нигде не сохраняются
+		None => reveal_roles_in_turn(&players_with_roles)?,
+	}
+
+	// Имена запоминаются, чтобы в следующей игре их можно было выбрать из списка
+	if let Err(e) = crate::wizard::remember_players(&setup.player_names) {
+		eprintln!("Предупреждение: Не удалось сохранить список игроков: {}", e);
+	}
+
+	Ok(())
+}
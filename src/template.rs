@@ -0,0 +1,152 @@
+use crate::error::AppError;
+use crate::role::Role;
+use chrono::Local;
+
+/// Шаблон файла роли по умолчанию (прежний фиксированный формат).
+pub const DEFAULT_TEMPLATE: &str = "Игрок: {player}\n\nВаша роль: {role}\n\nОписание:\n{description}\n{team}";
+
+/// Поддерживаемые подстановки в шаблоне.
+const PLACEHOLDERS: &[&str] = &["player", "role", "description", "faction", "teammates", "team", "seat", "date"];
+
+/// Данные одного игрока, которые подставляются в шаблон.
+pub struct TemplateContext<'a> {
+	pub player: &'a str,
+	pub role: Role,
+	/// Номер места за столом (с единицы).
+	pub seat: usize,
+	/// Известные игроку союзники в виде строки, например "Анна, Борис (Дон)".
+	pub teammates: String,
+}
+
+/// Шаблон текста файла роли с подстановками вида `{player}`.
+/// Чтобы вставить фигурную скобку, её нужно удвоить: `{{` или `}}`.
+#[derive(Debug, Clone)]
+pub struct RoleTemplate {
+	text: String,
+	date_format: DateFormat,
+}
+
+/// Формат даты в подстановке `{date}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateFormat {
+	/// ДД.ММ.ГГГГ
+	#[default]
+	DayMonthYear,
+	/// ГГГГ-ММ-ДД (ISO 8601)
+	Iso,
+}
+
+impl DateFormat {
+	/// Разбирает название формата: `dd.mm.yyyy` или `yyyy-mm-dd` (он же `iso`).
+	pub fn parse(value: &str) -> Option<Self> {
+		match value.trim().to_lowercase().as_str() {
+			"dd.mm.yyyy" => Some(DateFormat::DayMonthYear),
+			"yyyy-mm-dd" | "iso" => Some(DateFormat::Iso),
+			_ => None,
+		}
+	}
+}
+
+impl Default for RoleTemplate {
+	fn default() -> Self {
+		RoleTemplate { text: DEFAULT_TEMPLATE.to_string(), date_format: DateFormat::default() }
+	}
+}
+
+impl RoleTemplate {
+	/// Создаёт шаблон из текста и проверяет, что все подстановки известны.
+	pub fn parse(text: &str) -> Result<Self, AppError> {
+		let template = RoleTemplate { text: text.to_string(), date_format: DateFormat::default() };
+		// Пробная подстановка находит неизвестные имена и незакрытые скобки.
+		template.expand(|_| String::new())?;
+		Ok(template)
+	}
+
+	/// Загружает шаблон из файла.
+	pub fn from_file(path: &str) -> Result<Self, AppError> {
+		let text = std::fs::read_to_string(path)?;
+		Self::parse(&text)
+	}
+
+	/// Задаёт формат даты для подстановки `{date}`.
+	pub fn with_date_format(mut self, date_format: DateFormat) -> Self {
+		self.date_format = date_format;
+		self
+	}
+
+	/// Формирует текст файла роли для игрока.
+	pub fn render(&self, context: &TemplateContext) -> String {
+		let date = current_date(self.date_format);
+		self.expand(|name| match name {
+			"player" => context.player.to_string(),
+			"role" => context.role.get_name().to_string(),
+			"description" => context.role.get_description().to_string(),
+			"faction" => context.role.get_faction().get_name().to_string(),
+			"teammates" if context.teammates.is_empty() => "—".to_string(),
+			"teammates" => context.teammates.clone(),
+			// Готовый блок о команде: пустой для игроков, которые не знают союзников.
+			"team" if context.teammates.is_empty() => String::new(),
+			"team" => format!("\nВаша команда: {}\n", context.teammates),
+			"seat" => context.seat.to_string(),
+			"date" => date.clone(),
+			_ => String::new(),
+		})
+		// Шаблон проверен при создании, поэтому ошибок здесь быть не может.
+		.unwrap_or_default()
+	}
+
+	/// Проходит по шаблону и заменяет подстановки значениями из `lookup`.
+	fn expand(&self, lookup: impl Fn(&str) -> String) -> Result<String, AppError> {
+		let mut result = String::with_capacity(self.text.len());
+		let mut chars = self.text.chars().peekable();
+
+		while let Some(c) = chars.next() {
+			match c {
+				'{' if chars.peek() == Some(&'{') => {
+					chars.next();
+					result.push('{');
+				}
+				'}' if chars.peek() == Some(&'}') => {
+					chars.next();
+					result.push('}');
+				}
+				'{' => {
+					let mut name = String::new();
+					let mut closed = false;
+					for next in chars.by_ref() {
+						if next == '}' {
+							closed = true;
+							break;
+						}
+						name.push(next);
+					}
+					if !closed {
+						return Err(AppError::InvalidTemplate(format!("незакрытая подстановка '{{{name}'")));
+					}
+					if !PLACEHOLDERS.contains(&name.as_str()) {
+						return Err(AppError::InvalidTemplate(format!("неизвестная подстановка '{{{name}}}'")));
+					}
+					result.push_str(&lookup(&name));
+				}
+				'}' => {
+					return Err(AppError::InvalidTemplate("лишняя закрывающая скобка '}'".to_string()));
+				}
+				_ => result.push(c),
+			}
+		}
+
+		Ok(result)
+	}
+}
+
+/**
+ * Возвращает текущую дату в часовом поясе компьютера в заданном формате.
+ * Игры, начатые после полуночи, получают уже новую дату, а не дату по UTC.
+ */
+fn current_date(date_format: DateFormat) -> String {
+	let today = Local::now().date_naive();
+	match date_format {
+		DateFormat::DayMonthYear => today.format("%d.%m.%Y").to_string(),
+		DateFormat::Iso => today.format("%Y-%m-%d").to_string(),
+	}
+}
@@ -0,0 +1,501 @@
+//! Полноэкранный интерфейс ведущего в терминале (`--tui`).
+//!
+//! Слева — таблица игроков, которую можно править на месте, справа — состав ролей
+//! с проверкой баланса. После генерации роли показываются на том же экране
+//! по одному игроку, между игроками роль скрывается.
+
+use std::collections::HashSet;
+use mafia_game_generator::error::AppError;
+use mafia_game_generator::game_setup::{shuffle_roles, Assignment, GameMode};
+use mafia_game_generator::role::{Faction, Role, RoleCounts};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use crate::config::Config;
+use crate::io_handler::validate_player_name;
+use crate::wizard::{check_layout, mode_name, remember_players};
+
+/// Какая часть экрана настройки принимает ввод.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+	Players,
+	Roles,
+	Generate,
+}
+
+/// Экран интерфейса.
+enum Screen {
+	Setup,
+	/// Показ ролей: `shown` — роль текущего игрока открыта.
+	Reveal { assignment: Assignment, index: usize, shown: bool },
+	/// Все роли показаны; `host_list` — ведущий открыл список ролей.
+	Done { assignment: Assignment, host_list: bool },
+}
+
+/// Состояние интерфейса.
+struct App<'a> {
+	settings: &'a Config,
+	screen: Screen,
+	focus: Focus,
+	players: Vec<String>,
+	selected_player: usize,
+	game_mode: GameMode,
+	role_counts: RoleCounts,
+	/// Ведущий менял состав вручную: он больше не пересчитывается автоматически.
+	roles_edited: bool,
+	/// Строка панели ролей: 0 — режим игры, далее роли.
+	selected_role: usize,
+	message: Option<String>,
+	quit: bool,
+}
+
+/**
+ * Запускает полноэкранный интерфейс и восстанавливает терминал после выхода.
+ */
+pub fn run(settings: &Config) -> Result<(), AppError> {
+	let mut app = App::new(settings)?;
+	let mut terminal = ratatui::init();
+	let result = app.run(&mut terminal);
+	ratatui::restore();
+	result
+}
+
+impl<'a> App<'a> {
+	fn new(settings: &'a Config) -> Result<Self, AppError> {
+		let game_mode = settings.game_mode()?;
+		// Пока игроков слишком мало, показывается состав для наименьшего их количества.
+		let min_players = settings.game_config(0, game_mode)?.min_players;
+		let mut app = App {
+			settings,
+			screen: Screen::Setup,
+			focus: Focus::Players,
+			players: vec![String::new()],
+			selected_player: 0,
+			game_mode,
+			role_counts: Role::get_role_counts(&settings.game_config(min_players, game_mode)?)?,
+			roles_edited: false,
+			selected_role: 0,
+			message: None,
+			quit: false,
+		};
+		app.suggest_roles()?;
+		Ok(app)
+	}
+
+	fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), AppError> {
+		while !self.quit {
+			terminal.draw(|frame| self.draw(frame))?;
+			if let Event::Key(key) = event::read()?
+				&& key.kind != KeyEventKind::Release
+			{
+				self.handle_key(key)?;
+			}
+		}
+		Ok(())
+	}
+
+	// Количество игроков: пустые строки таблицы не учитываются.
+	fn player_count(&self) -> usize {
+		self.players.iter().filter(|name| !name.trim().is_empty()).count()
+	}
+
+	// Пересчитывает предложенный состав, пока ведущий не изменил его вручную.
+	fn suggest_roles(&mut self) -> Result<(), AppError> {
+		let config = self.settings.game_config(self.player_count() as u8, self.game_mode)?;
+		if !self.roles_edited && (config.min_players..=config.max_players).contains(&config.player_count) {
+			self.role_counts = Role::get_role_counts(&config)?;
+		}
+		Ok(())
+	}
+
+	fn handle_key(&mut self, key: KeyEvent) -> Result<(), AppError> {
+		if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+			self.quit = true;
+			return Ok(());
+		}
+		self.message = None;
+
+		match &mut self.screen {
+			Screen::Setup => self.handle_setup_key(key)?,
+			Screen::Reveal { assignment, index, shown } => {
+				if matches!(key.code, KeyCode::Enter | KeyCode::Char(' ')) {
+					if !*shown {
+						*shown = true;
+					} else if *index + 1 < assignment.players.len() {
+						*index += 1;
+						*shown = false;
+					} else {
+						let assignment = assignment.clone();
+						self.screen = Screen::Done { assignment, host_list: false };
+					}
+				}
+			}
+			Screen::Done { host_list, .. } => match key.code {
+				KeyCode::Esc => self.quit = true,
+				KeyCode::Enter => self.screen = Screen::Setup,
+				KeyCode::Char('l' | 'L' | 'д' | 'Д') => *host_list = !*host_list,
+				_ => {}
+			},
+		}
+		Ok(())
+	}
+
+	fn handle_setup_key(&mut self, key: KeyEvent) -> Result<(), AppError> {
+		match key.code {
+			KeyCode::Esc => self.quit = true,
+			KeyCode::Tab => {
+				self.focus = match self.focus {
+					Focus::Players => Focus::Roles,
+					Focus::Roles => Focus::Generate,
+					Focus::Generate => Focus::Players,
+				};
+			}
+			KeyCode::BackTab => {
+				self.focus = match self.focus {
+					Focus::Players => Focus::Generate,
+					Focus::Roles => Focus::Players,
+					Focus::Generate => Focus::Roles,
+				};
+			}
+			KeyCode::F(5) => self.generate()?,
+			_ => match self.focus {
+				Focus::Players => self.edit_players(key)?,
+				Focus::Roles => self.edit_roles(key)?,
+				Focus::Generate => {
+					if matches!(key.code, KeyCode::Enter | KeyCode::Char(' ')) {
+						self.generate()?;
+					}
+				}
+			},
+		}
+		Ok(())
+	}
+
+	fn edit_players(&mut self, key: KeyEvent) -> Result<(), AppError> {
+		let max_players = self.settings.game_config(0, self.game_mode)?.max_players as usize;
+		match key.code {
+			KeyCode::Up => self.selected_player = self.selected_player.saturating_sub(1),
+			KeyCode::Down if self.selected_player + 1 < self.players.len() => self.selected_player += 1,
+			// Enter на последней строке добавляет нового игрока.
+			KeyCode::Enter | KeyCode::Down => {
+				if self.selected_player + 1 < self.players.len() {
+					self.selected_player += 1;
+				} else if self.player_count() >= max_players {
+					self.message = Some(format!("Игроков не может быть больше {max_players}."));
+				} else if !self.players[self.selected_player].trim().is_empty() {
+					self.players.push(String::new());
+					self.selected_player += 1;
+				}
+			}
+			KeyCode::Insert if self.player_count() < max_players => {
+				self.players.insert(self.selected_player + 1, String::new());
+				self.selected_player += 1;
+			}
+			KeyCode::Delete => {
+				self.players.remove(self.selected_player);
+				if self.players.is_empty() {
+					self.players.push(String::new());
+				}
+				self.selected_player = self.selected_player.min(self.players.len() - 1);
+			}
+			KeyCode::Backspace => {
+				self.players[self.selected_player].pop();
+			}
+			KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+				self.players[self.selected_player].push(c);
+			}
+			_ => return Ok(()),
+		}
+		self.suggest_roles()
+	}
+
+	fn edit_roles(&mut self, key: KeyEvent) -> Result<(), AppError> {
+		let entries = self.role_counts.entries();
+		match key.code {
+			KeyCode::Up => self.selected_role = self.selected_role.saturating_sub(1),
+			KeyCode::Down => self.selected_role = (self.selected_role + 1).min(entries.len()),
+			KeyCode::Left | KeyCode::Right | KeyCode::Char('-' | '+') if self.selected_role == 0 => {
+				self.game_mode = match self.game_mode {
+					GameMode::Classic => GameMode::Extended,
+					GameMode::Extended => GameMode::Classic,
+				};
+				self.suggest_roles()?;
+			}
+			KeyCode::Left | KeyCode::Char('-') => {
+				let (role, count) = entries[self.selected_role - 1].clone();
+				self.role_counts.set_count(role, count.saturating_sub(1));
+				self.roles_edited = true;
+			}
+			KeyCode::Right | KeyCode::Char('+') => {
+				let (role, count) = entries[self.selected_role - 1].clone();
+				if (count as usize) < self.player_count() {
+					self.role_counts.set_count(role, count + 1);
+					self.roles_edited = true;
+				}
+			}
+			KeyCode::Char('r' | 'R' | 'к' | 'К') => {
+				self.roles_edited = false;
+				self.suggest_roles()?;
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+
+	// Проверяет игроков и состав ролей и переходит к показу ролей.
+	fn generate(&mut self) -> Result<(), AppError> {
+		let limits = self.settings.game_config(0, self.game_mode)?;
+		let player_count = self.player_count();
+		if !(limits.min_players as usize..=limits.max_players as usize).contains(&player_count) {
+			self.message = Some(format!(
+				"Для игры требуется от {} до {} игроков, сейчас {}.",
+				limits.min_players, limits.max_players, player_count
+			));
+			return Ok(());
+		}
+
+		let mut names = Vec::with_capacity(player_count);
+		let mut unique_names = HashSet::with_capacity(player_count);
+		for (index, name) in self.players.iter().enumerate().filter(|(_, name)| !name.trim().is_empty()) {
+			match validate_player_name(name, &unique_names) {
+				Ok(name) => {
+					unique_names.insert(name.to_lowercase());
+					names.push(name);
+				}
+				Err(e) => {
+					self.focus = Focus::Players;
+					self.selected_player = index;
+					self.message = Some(e.to_string());
+					return Ok(());
+				}
+			}
+		}
+
+		if let Err(msg) = check_layout(&self.role_counts, player_count as u8) {
+			self.focus = Focus::Roles;
+			self.message = Some(msg);
+			return Ok(());
+		}
+
+		let mut roles = self.role_counts.to_vec();
+		shuffle_roles(&mut roles);
+		if let Err(e) = remember_players(&names) {
+			self.message = Some(format!("Не удалось сохранить список игроков: {e}"));
+		}
+		self.screen = Screen::Reveal { assignment: Assignment::new(names, roles), index: 0, shown: false };
+		Ok(())
+	}
+
+	fn draw(&self, frame: &mut Frame) {
+		let [main, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(2)]).areas(frame.area());
+
+		let hints = match &self.screen {
+			Screen::Setup => match self.focus {
+				Focus::Players => "Введите имя, Enter — следующий игрок, Insert — вставить, Delete — удалить, Tab — роли, F5 — генерация, Esc — выход",
+				Focus::Roles => "↑↓ — выбор, ←→ — изменить, R — предложенный состав, Tab — кнопка, F5 — генерация, Esc — выход",
+				Focus::Generate => "Enter — сгенерировать роли, Tab — игроки, Esc — выход",
+			},
+			Screen::Reveal { .. } => "Enter — дальше, Ctrl+C — выход",
+			Screen::Done { .. } => "L — список ролей для ведущего, Enter — новая игра, Esc — выход",
+		};
+		let mut status_lines = vec![Line::from(hints).dark_gray()];
+		if let Some(message) = &self.message {
+			status_lines.insert(0, Line::from(message.as_str()).red());
+		}
+		frame.render_widget(Paragraph::new(status_lines).wrap(Wrap { trim: true }), status);
+
+		match &self.screen {
+			Screen::Setup => self.draw_setup(frame, main),
+			Screen::Reveal { assignment, index, shown } => draw_reveal(frame, main, assignment, *index, *shown),
+			Screen::Done { assignment, host_list } => draw_done(frame, main, assignment, *host_list),
+		}
+	}
+
+	fn draw_setup(&self, frame: &mut Frame, area: Rect) {
+		let [left, right] = Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(area);
+		let [roles_area, button_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(right);
+
+		// Таблица игроков: ошибки в именах видны сразу.
+		let mut unique_names = HashSet::new();
+		let rows: Vec<Row> = self
+			.players
+			.iter()
+			.enumerate()
+			.map(|(index, name)| {
+				let editing = self.focus == Focus::Players && index == self.selected_player;
+				let cursor = if editing { "▏" } else { "" };
+				let (status, style) = match validate_player_name(name, &unique_names) {
+					Ok(valid) => {
+						unique_names.insert(valid.to_lowercase());
+						(String::new(), Style::default())
+					}
+					Err(_) if name.trim().is_empty() => (String::new(), Style::default()),
+					Err(e) => (e.to_string(), Style::default().fg(Color::Red)),
+				};
+				Row::new(vec![
+					Cell::from(format!("{}", index + 1)),
+					Cell::from(format!("{name}{cursor}")).style(style),
+					Cell::from(status).style(style),
+				])
+			})
+			.collect();
+		let table = Table::new(rows, [Constraint::Length(3), Constraint::Percentage(45), Constraint::Fill(1)])
+			.header(Row::new(["№", "Имя", ""]).bold())
+			.row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+			.block(panel(format!("Игроки ({})", self.player_count()), self.focus == Focus::Players));
+		let mut state = TableState::default().with_selected(Some(self.selected_player));
+		frame.render_stateful_widget(table, left, &mut state);
+
+		// Состав ролей и оценка баланса.
+		let mut lines = vec![role_line(
+			format!("Режим: {}", mode_name(self.game_mode)),
+			self.focus == Focus::Roles && self.selected_role == 0,
+		)];
+		for (index, (role, count)) in self.role_counts.entries().into_iter().enumerate() {
+			let text = format!("{} {:<16} {:>2}", role.get_icon(), role.get_name(), count);
+			let line = role_line(text, self.focus == Focus::Roles && self.selected_role == index + 1);
+			lines.push(if count == 0 { line.dark_gray() } else { line });
+		}
+		lines.push(Line::default());
+		lines.extend(balance_lines(&self.role_counts, self.player_count()));
+		if self.roles_edited {
+			lines.push(Line::from("Состав изменен вручную (R — вернуть предложенный)").dark_gray());
+		}
+		frame.render_widget(
+			Paragraph::new(lines).wrap(Wrap { trim: false }).block(panel("Роли".to_string(), self.focus == Focus::Roles)),
+			roles_area,
+		);
+
+		let button = Paragraph::new(Line::from("[ Сгенерировать роли ]").bold())
+			.alignment(Alignment::Center)
+			.block(panel(String::new(), self.focus == Focus::Generate));
+		frame.render_widget(button, button_area);
+	}
+}
+
+// Показ роли одному игроку или экран, скрывающий её перед следующим.
+fn draw_reveal(frame: &mut Frame, area: Rect, assignment: &Assignment, index: usize, shown: bool) {
+	let (name, role) = &assignment.players[index];
+	let total = assignment.players.len();
+	let mut lines = vec![Line::from(format!("Игрок {} из {}", index + 1, total)).dark_gray(), Line::default()];
+
+	if shown {
+		lines.push(Line::from(format!("{name}, ваша роль:")));
+		lines.push(Line::default());
+		lines.push(Line::from(format!("{} {}", role.get_icon(), role.get_name())).bold().fg(faction_color(role.get_faction())));
+		lines.push(Line::default());
+		lines.push(Line::from(role.get_description()));
+		lines.push(Line::from(format!("Сторона: {}", role.get_faction().get_name())));
+		let allies = assignment.describe_allies(index);
+		if !allies.is_empty() {
+			lines.push(Line::from(format!("Ваша команда: {allies}")));
+		}
+		lines.push(Line::default());
+		lines.push(Line::from("Запомните роль и нажмите Enter, чтобы скрыть её.").dark_gray());
+	} else {
+		if index > 0 {
+			lines.push(Line::from("Роль предыдущего игрока скрыта.").dark_gray());
+			lines.push(Line::default());
+		}
+		lines.push(Line::from(vec![Span::raw("Передайте экран игроку "), Span::from(name.as_str()).bold()]));
+		lines.push(Line::default());
+		lines.push(Line::from("Нажмите Enter, когда рядом не будет других игроков.").dark_gray());
+	}
+
+	let paragraph = Paragraph::new(Text::from(lines))
+		.alignment(Alignment::Center)
+		.wrap(Wrap { trim: true })
+		.block(Block::bordered().title(" Показ ролей "));
+	frame.render_widget(paragraph, centered(area, 70, 18));
+}
+
+// Завершающий экран и список ролей для ведущего.
+fn draw_done(frame: &mut Frame, area: Rect, assignment: &Assignment, host_list: bool) {
+	if !host_list {
+		let paragraph = Paragraph::new(vec![
+			Line::from("Все игроки узнали свои роли.").bold(),
+			Line::default(),
+			Line::from("Нажмите L, чтобы открыть список ролей для ведущего.").dark_gray(),
+		])
+		.alignment(Alignment::Center)
+		.block(Block::bordered().title(" Показ ролей "));
+		frame.render_widget(paragraph, centered(area, 70, 7));
+		return;
+	}
+
+	let rows = assignment.players.iter().enumerate().map(|(index, (name, role))| {
+		Row::new(vec![
+			Cell::from(format!("{}", index + 1)),
+			Cell::from(name.as_str()),
+			Cell::from(format!("{} {}", role.get_icon(), role.get_name())).style(Style::default().fg(faction_color(role.get_faction()))),
+		])
+	});
+	let table = Table::new(rows, [Constraint::Length(3), Constraint::Percentage(50), Constraint::Fill(1)])
+		.header(Row::new(["№", "Игрок", "Роль"]).bold())
+		.block(Block::bordered().title(" Роли для ведущего "));
+	frame.render_widget(table, area);
+}
+
+// Оценка состава: сколько ролей у каждой стороны и подходит ли состав для игры.
+fn balance_lines(counts: &RoleCounts, player_count: usize) -> Vec<Line<'static>> {
+	let mut sides = [0usize; 3];
+	for (role, count) in counts.entries() {
+		let side = match role.get_faction() {
+			Faction::Town => 0,
+			Faction::Mafia => 1,
+			Faction::Neutral => 2,
+		};
+		sides[side] += count as usize;
+	}
+	let total: usize = sides.iter().sum();
+
+	let mut lines = vec![
+		Line::from(format!("Ролей: {total} из {player_count}")),
+		Line::from(format!("Город: {}  Мафия: {}  Одиночки: {}", sides[0], sides[1], sides[2])),
+	];
+	match u8::try_from(player_count).map_err(|_| String::new()).and_then(|count| check_layout(counts, count)) {
+		Ok(()) => lines.push(Line::from("✓ Состав подходит для игры").green()),
+		Err(msg) => lines.push(Line::from(format!("✗ {msg}")).red()),
+	}
+	lines
+}
+
+fn role_line(text: String, selected: bool) -> Line<'static> {
+	if selected {
+		Line::from(format!("▶ {text}")).reversed()
+	} else {
+		Line::from(format!("  {text}"))
+	}
+}
+
+fn panel(title: String, focused: bool) -> Block<'static> {
+	let block = Block::bordered().title(if title.is_empty() { title } else { format!(" {title} ") });
+	if focused {
+		block.border_style(Style::default().fg(Color::Yellow))
+	} else {
+		block
+	}
+}
+
+fn faction_color(faction: Faction) -> Color {
+	match faction {
+		Faction::Town => Color::Green,
+		Faction::Mafia => Color::Red,
+		Faction::Neutral => Color::Magenta,
+	}
+}
+
+// Прямоугольник заданной ширины (в процентах) и высоты по центру области.
+fn centered(area: Rect, percent_x: u16, height: u16) -> Rect {
+	let [_, middle, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(height), Constraint::Fill(1)]).areas(area);
+	let [_, center, _] = Layout::horizontal([
+		Constraint::Percentage((100 - percent_x) / 2),
+		Constraint::Percentage(percent_x),
+		Constraint::Percentage((100 - percent_x) / 2),
+	])
+	.areas(middle);
+	center
+}
@@ -1,316 +1,1008 @@
 use std::env;
-use std::time::Duration;
-use std::process::{Command};
-use std::path::Path;
-use std::fs::File;
-use fs2::FileExt;
-
-const GITHUB_URL: &str = "https://raw.githubusercontent.com/Stive99/MafiaGameGenerator";
-const DOWNLOAD_URL: &str = "https://github.com/Stive99/MafiaGameGenerator/releases/download";
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+use std::process::{Child, Command};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use mafia_game_generator::error::AppError;
+use minisign_verify::{PublicKey, Signature};
+use sha2::{Digest, Sha256};
+use crate::config::{env_value, Config};
+use crate::instance;
+use crate::paths;
+use crate::version::Version;
+
+// Сведения о последнем опубликованном релизе (черновики и пре-релизы не возвращаются).
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/Stive99/MafiaGameGenerator/releases/latest";
+// Список последних релизов, включая пре-релизы (для бета-канала).
+const RELEASES_URL: &str = "https://api.github.com/repos/Stive99/MafiaGameGenerator/releases?per_page=30";
+// Переменная окружения с источником обновлений (адрес зеркала или file://папка).
+const UPDATE_SOURCE_ENV: &str = "MAFIA_UPDATE_SOURCE";
+// Переменная окружения с каналом обновлений (stable или beta).
+const UPDATE_CHANNEL_ENV: &str = "MAFIA_UPDATE_CHANNEL";
+// Переменная окружения, отключающая автоматическую проверку обновлений.
+const NO_UPDATE_CHECK_ENV: &str = "MAFIA_NO_UPDATE_CHECK";
+// Автоматическая проверка выполняется не чаще раза в сутки.
+const CHECK_INTERVAL_SECS: u64 = 24 * 60 * 60;
+// Загрузка прерывается, если сервер не присылает данные дольше этого времени.
+const DOWNLOAD_READ_TIMEOUT: Duration = Duration::from_secs(30);
+// Ширина индикатора загрузки в символах.
+const PROGRESS_WIDTH: usize = 30;
+// Сколько ждать незавершённую фоновую проверку после окончания сессии.
+const BACKGROUND_CHECK_GRACE: Duration = Duration::from_secs(3);
+// Файл с временем последней автоматической проверки (в каталоге состояния).
+const LAST_CHECK_FILE_NAME: &str = "last-update-check";
+// Журнал незавершенного обновления (в каталоге состояния).
+const JOURNAL_FILE_NAME: &str = "update-journal";
+// Флаг запуска новой версии сразу после установки.
+const UPDATED_FLAG: &str = "--updated";
+// Флаг запуска новой версии только для самопроверки.
+const HEALTH_CHECK_FLAG: &str = "--health-check";
+// Сколько ждать подтверждения запуска от новой версии.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(200);
+// Файл с описанием релиза в зеркале; формат совпадает с ответом GitHub Releases API.
+const MIRROR_MANIFEST: &str = "latest.json";
+// Файл с описанием последнего пре-релиза в зеркале (необязательный).
+const MIRROR_BETA_MANIFEST: &str = "latest-beta.json";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/141.0.0.0 Safari/537.36";
-const APP_NAME: &str = "MafiaGameGenerator.exe";
-const NEW_APP_NAME: &str = "MafiaGameGenerator_new.exe";
-const BACKUP_APP_NAME: &str = "MafiaGameGenerator_old.exe";
-const LOCK_FILE_NAME: &str = "MafiaGameGenerator.lock";
+// Файлы релиза называются MafiaGameGenerator-<os>-<arch>[.exe], например MafiaGameGenerator-linux-x86_64.
+const ASSET_PREFIX: &str = "MafiaGameGenerator";
+// Имя файла в старых релизах, где публиковалась только сборка для Windows.
+const LEGACY_WINDOWS_ASSET: &str = "MafiaGameGenerator.exe";
+// Манифест контрольных сумм SHA-256 всех файлов релиза (формат утилиты sha256sum).
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+// Подпись манифеста, созданная `minisign -S -m SHA256SUMS -t "version=<версия>"`.
+// Версия в доверенном комментарии подписана вместе с манифестом.
+const SIGNATURE_ASSET: &str = "SHA256SUMS.minisig";
+// Ключ в доверенном комментарии подписи, которому соответствует версия релиза.
+const SIGNED_VERSION_KEY: &str = "version=";
+// Публичный ключ minisign, которым подписываются релизы проекта. Задается при сборке релиза
+// переменной окружения MAFIA_UPDATE_PUBLIC_KEY; сборка без ключа обновления не устанавливает.
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("MAFIA_UPDATE_PUBLIC_KEY");
+
+/// Пути к исполняемым файлам приложения при обновлении.
+struct AppPaths {
+	/// Текущий исполняемый файл.
+	current: PathBuf,
+	/// Новая версия, скопированная из кэша перед заменой (`<имя>_new`).
+	new: PathBuf,
+	/// Резервная копия предыдущей версии (`<имя>_old`).
+	backup: PathBuf,
+}
+
+// Определяет пути по реальному имени текущего исполняемого файла.
+// Пути запоминаются при первом вызове: после переименования запущенного файла
+// current_exe() в Linux возвращает уже имя резервной копии.
+fn app_paths() -> Result<&'static AppPaths, Box<dyn std::error::Error + Send + Sync>> {
+	static APP_PATHS: OnceLock<AppPaths> = OnceLock::new();
+	if let Some(paths) = APP_PATHS.get() {
+		return Ok(paths);
+	}
+
+	let current = std::env::current_exe()?;
+	let current_dir = current.parent().ok_or("Не удалось определить директорию приложения")?;
+	let stem = current
+		.file_stem()
+		.ok_or("Не удалось определить имя исполняемого файла")?
+		.to_string_lossy()
+		.into_owned();
+
+	let new = current_dir.join(format!("{}_new{}", stem, env::consts::EXE_SUFFIX));
+	let backup = current_dir.join(format!("{}_old{}", stem, env::consts::EXE_SUFFIX));
+	Ok(APP_PATHS.get_or_init(|| AppPaths { current, new, backup }))
+}
+
+// Имя файла релиза для текущей операционной системы и архитектуры.
+fn release_asset_name() -> String {
+	format!("{}-{}-{}{}", ASSET_PREFIX, env::consts::OS, env::consts::ARCH, env::consts::EXE_SUFFIX)
+}
+
+// Разрешает запуск загруженного файла (в Windows не требуется).
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+	use std::os::unix::fs::PermissionsExt;
+	std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+	Ok(())
+}
 
+/// Релиз из GitHub Releases API.
 #[derive(serde::Deserialize, Debug)]
-struct CargoToml {
-	package: Package,
+struct Release {
+	/// Тег релиза, например `v0.3.0`.
+	tag_name: String,
+	/// Описание изменений (Markdown).
+	body: Option<String>,
+	/// Файлы, прикреплённые к релизу.
+	assets: Vec<ReleaseAsset>,
+	/// Релиз помечен как пре-релиз.
+	#[serde(default)]
+	prerelease: bool,
+	/// Черновик, ещё не опубликованный релиз.
+	#[serde(default)]
+	draft: bool,
 }
 
 #[derive(serde::Deserialize, Debug)]
-struct Package {
-	version: String,
+struct ReleaseAsset {
+	name: String,
+	/// Адрес загрузки. В зеркалах не используется: файл всегда ищется рядом с `latest.json`.
+	#[serde(default)]
+	browser_download_url: String,
+}
+
+impl Release {
+	/// Версия релиза без префикса `v`.
+	fn version(&self) -> &str {
+		self.tag_name.trim_start_matches('v')
+	}
+
+	/// Ищет файл релиза по имени.
+	fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+		self.assets.iter().find(|asset| asset.name == name)
+	}
+}
+
+/// Канал обновлений.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateChannel {
+	/// Только стабильные релизы.
+	Stable,
+	/// Стабильные релизы и пре-релизы для тестирования.
+	Beta,
+}
+
+impl UpdateChannel {
+	/// Разбирает строку `stable` или `beta`.
+	pub fn parse(value: &str) -> Result<Self, AppError> {
+		match value.trim().to_lowercase().as_str() {
+			"stable" => Ok(UpdateChannel::Stable),
+			"beta" => Ok(UpdateChannel::Beta),
+			_ => Err(AppError::UpdateConfig(format!(
+				"неизвестный канал обновлений '{}'. Допустимые значения: stable, beta",
+				value.trim()
+			))),
+		}
+	}
 }
 
-pub async fn fetch_remote_version() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-	let client = reqwest::Client::builder()
-		.timeout(Duration::from_secs(10))
-		.build()?;
+/// Настройки проверки обновлений.
+#[derive(Debug, Clone)]
+pub struct UpdateSettings {
+	pub source: UpdateSource,
+	pub channel: UpdateChannel,
+	/// Проверять ли обновления автоматически в интерактивном режиме.
+	pub auto_check: bool,
+}
 
-	let url = format!("{}/main/Cargo.toml", GITHUB_URL);
-	// println!("Проверка обновлений по адресу: {}", url);
+impl UpdateSettings {
+	/// Определяет настройки обновления: параметры командной строки, затем переменные
+	/// окружения MAFIA_UPDATE_SOURCE и MAFIA_UPDATE_CHANNEL, затем раздел `[update]`
+	/// файла настроек, иначе GitHub и стабильный канал.
+	/// Автоматическую проверку отключает переменная MAFIA_NO_UPDATE_CHECK (кроме значений 0 и false)
+	/// или параметр `auto_check = false`.
+	pub fn resolve(cli_source: Option<&str>, cli_channel: Option<&str>, config: &Config) -> Result<Self, AppError> {
+		let file = &config.update;
+		let source = match cli_source.map(str::to_string).or(env_value(UPDATE_SOURCE_ENV)?).or_else(|| file.source.clone()) {
+			Some(value) => UpdateSource::parse(&value)?,
+			None => UpdateSource::GitHub,
+		};
+		let channel = match cli_channel.map(str::to_string).or(env_value(UPDATE_CHANNEL_ENV)?).or_else(|| file.channel.clone()) {
+			Some(value) => UpdateChannel::parse(&value)?,
+			None => UpdateChannel::Stable,
+		};
+		let auto_check = match env_value(NO_UPDATE_CHECK_ENV)? {
+			Some(value) => matches!(value.trim().to_lowercase().as_str(), "0" | "false"),
+			None => file.auto_check.unwrap_or(true),
+		};
+		Ok(UpdateSettings { source, channel, auto_check })
+	}
+}
 
-	let request = client
-		.get(&url)
-		.header("User-Agent", USER_AGENT)
-		.header("Accept", "text/plain")
-		.build()?;
+impl fmt::Display for UpdateChannel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			UpdateChannel::Stable => write!(f, "stable"),
+			UpdateChannel::Beta => write!(f, "beta"),
+		}
+	}
+}
 
-	let response = client.execute(request).await?;
-	// println!("Статус ответа: {}", response.status());
+/// Откуда загружаются сведения о релизах и сами файлы.
+#[derive(Debug, Clone)]
+pub enum UpdateSource {
+	/// Релизы репозитория на GitHub.
+	GitHub,
+	/// HTTP-зеркало: `<адрес>/latest.json` и файлы релиза рядом с ним.
+	Mirror(String),
+	/// Локальная или сетевая папка с той же структурой, что и зеркало.
+	Directory(PathBuf),
+}
 
-	// Проверить, успешен ли ответ.
-	if response.status().is_success() {
-		let cargo_toml_content = response.text().await?;
-		// println!("Получено содержимое длиной: {} байт", cargo_toml_content.len());
+impl fmt::Display for UpdateSource {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			UpdateSource::GitHub => write!(f, "github"),
+			UpdateSource::Mirror(url) => write!(f, "{url}"),
+			UpdateSource::Directory(path) => write!(f, "file://{}", path.display()),
+		}
+	}
+}
 
-		// Проверить, что содержимое не пустое.
-		if cargo_toml_content.trim().is_empty() {
-			return Err("Получено пустое содержимое от сервера".into());
+impl UpdateSource {
+	/// Разбирает строку вида `github`, `http(s)://адрес` или `file://путь`.
+	pub fn parse(value: &str) -> Result<Self, AppError> {
+		let value = value.trim();
+		if value.eq_ignore_ascii_case("github") {
+			return Ok(UpdateSource::GitHub);
 		}
+		if value.starts_with("http://") || value.starts_with("https://") {
+			return Ok(UpdateSource::Mirror(value.trim_end_matches('/').to_string()));
+		}
+		if let Some(path) = value.strip_prefix("file://") {
+			// file:///C:/Updates -> C:/Updates
+			let path = match path.strip_prefix('/') {
+				Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest,
+				_ => path,
+			};
+			return Ok(UpdateSource::Directory(PathBuf::from(path)));
+		}
+		Err(AppError::UpdateConfig(format!(
+			"неизвестный источник обновлений '{value}'. Допустимые значения: github, http(s)://адрес, file://папка"
+		)))
+	}
+
+	// Выбирает самый новый релиз, доступный в канале.
+	async fn fetch_latest_release(&self, channel: UpdateChannel) -> Result<Release, Box<dyn std::error::Error + Send + Sync>> {
+		let candidates: Vec<Release> = match (self, channel) {
+			(UpdateSource::GitHub, UpdateChannel::Stable) => self.fetch_document(LATEST_RELEASE_URL).await?.into_iter().collect(),
+			(UpdateSource::GitHub, UpdateChannel::Beta) => self.fetch_document(RELEASES_URL).await?.unwrap_or_default(),
+			(_, UpdateChannel::Stable) => self.fetch_document(MIRROR_MANIFEST).await?.into_iter().collect(),
+			(_, UpdateChannel::Beta) => {
+				// Бета-канал получает и стабильные релизы, если они новее последнего пре-релиза.
+				let mut candidates: Vec<Release> = self.fetch_document(MIRROR_BETA_MANIFEST).await?.into_iter().collect();
+				candidates.extend(self.fetch_document(MIRROR_MANIFEST).await?);
+				candidates
+			}
+		};
+
+		candidates
+			.into_iter()
+			.filter(|release| !release.draft)
+			.filter_map(|release| Some((Version::parse(release.version())?, release)))
+			.filter(|(version, release)| {
+				channel == UpdateChannel::Beta || !(release.prerelease || version.is_prerelease())
+			})
+			.max_by(|(a, _), (b, _)| a.cmp(b))
+			.map(|(_, release)| release)
+			.ok_or_else(|| "Опубликованные релизы не найдены".into())
+	}
 
-		let cargo_toml = toml::from_str::<CargoToml>(&cargo_toml_content)?;
-		Ok(cargo_toml.package.version)
-	} else {
-		let status = response.status();
-		let error_text = response.text().await.unwrap_or_else(|_| "Нет текста ошибки".to_string());
-		Err(format!("Ошибка вернула статус: {} - {}", status, error_text).into())
-	}
-}
-
-pub async fn check_for_update() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	match fetch_remote_version().await {
-		Ok(remote_version) => {
-			// Используйте правильное сравнение версий.
-			if is_newer_version(&remote_version, CURRENT_VERSION) {
-				println!("Доступна новая версия: {} (текущая: {})", remote_version, CURRENT_VERSION);
-
-				// Автоматически загрузить и установить обновление.
-				match download_update(&remote_version).await {
-					Ok(()) => {
-						// println!("Обновление успешно загружено!");
-						// Применить обновление.
-						match apply_update() {
-							Ok(true) => {
-								println!("Обновление успешно установлено!\nНовая версия будет доступна при следующем запуске!");
-								// Запустить новую версию и завершить текущий процесс.
-								launch_new_version()?;
-								std::process::exit(0);
-							}
-							Ok(false) => {
-								println!("Не удалось установить обновление. Файл сохранен как '{}'", NEW_APP_NAME);
-							}
-							Err(e) => {
-								eprintln!("Ошибка при установке обновления: {}", e);
-								// Попробуйте отменить изменения.
-								if let Err(rollback_err) = rollback_update() {
-									eprintln!("Ошибка при откате обновления: {}", rollback_err);
-								}
-								return Err(e);
-							}
-						}
-					}
-					Err(e) => {
-						println!("Не удалось загрузить обновление: {}", e);
-						// println!("Вы можете загрузить последнюю версию вручную с:");
-						// println!("https://github.com/Stive99/MafiaGameGenerator/releases");
-					}
+	// Загружает JSON-документ источника: адрес для GitHub или имя файла для зеркала и папки.
+	// Возвращает None, если документа нет.
+	async fn fetch_document<T: serde::de::DeserializeOwned>(
+		&self,
+		location: &str,
+	) -> Result<Option<T>, Box<dyn std::error::Error + Send + Sync>> {
+		let url = match self {
+			UpdateSource::GitHub => location.to_string(),
+			UpdateSource::Mirror(base) => format!("{}/{}", base, location),
+			UpdateSource::Directory(dir) => {
+				let path = dir.join(location);
+				if !path.exists() {
+					return Ok(None);
 				}
-			} else {
-				// println!("Установлена последняя версия: {}", CURRENT_VERSION);
+				let content = std::fs::read_to_string(path)?;
+				return Ok(Some(serde_json::from_str(&content)?));
 			}
-			Ok(())
+		};
+
+		let client = reqwest::Client::builder()
+			.timeout(Duration::from_secs(10))
+			.build()?;
+
+		// println!("Проверка обновлений по адресу: {}", url);
+
+		let request = client
+			.get(&url)
+			.header("User-Agent", USER_AGENT)
+			.header("Accept", "application/vnd.github+json")
+			.build()?;
+
+		let response = client.execute(request).await?;
+		// println!("Статус ответа: {}", response.status());
+
+		// Проверить, успешен ли ответ.
+		if response.status().is_success() {
+			Ok(Some(response.json::<T>().await?))
+		} else if response.status() == reqwest::StatusCode::NOT_FOUND {
+			Ok(None)
+		} else {
+			let status = response.status();
+			let error_text = response.text().await.unwrap_or_else(|_| "Нет текста ошибки".to_string());
+			Err(format!("Ошибка вернула статус: {} - {}", status, error_text).into())
 		}
-		Err(e) => {
-			Err(format!("Не удалось проверить обновления: {}", e).into())
+	}
+
+	// Адрес файла релиза в HTTP-источнике. Зеркало всегда отдает файлы рядом с `latest.json`:
+	// адрес из скопированного с GitHub описания увел бы загрузку из закрытой сети.
+	fn asset_url(&self, asset: &ReleaseAsset) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+		match self {
+			UpdateSource::Mirror(base) => Ok(format!("{}/{}", base, asset.name)),
+			_ if !asset.browser_download_url.is_empty() => Ok(asset.browser_download_url.clone()),
+			_ => Err(format!("Не указан адрес загрузки файла '{}'", asset.name).into()),
 		}
 	}
-}
 
-// Вспомогательная функция для сравнения версий.
-fn is_newer_version(remote: &str, current: &str) -> bool {
-	let remote_parts: Vec<&str> = remote.split('.').collect();
-	let current_parts: Vec<&str> = current.split('.').collect();
-
-	// Сравнить основные, второстепенные и исправленные версии.
-	for (r, c) in remote_parts.iter().zip(current_parts.iter()) {
-		match (r.parse::<u32>(), c.parse::<u32>()) {
-			(Ok(r_num), Ok(c_num)) => {
-				if r_num > c_num {
-					return true;
-				} else if r_num < c_num {
-					return false;
-				}
-				// Если равны, переходите к следующей части.
+	// Загружает небольшой файл релиза (манифест, подпись) целиком в память.
+	async fn fetch_asset(&self, asset: &ReleaseAsset) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+		if let UpdateSource::Directory(dir) = self {
+			return Ok(std::fs::read(dir.join(&asset.name))?);
+		}
+		let url = self.asset_url(asset)?;
+
+		let client = reqwest::Client::builder()
+			.timeout(Duration::from_secs(10))
+			.build()?;
+
+		let request = client
+			.get(&url)
+			.header("User-Agent", USER_AGENT)
+			.header("Accept", "application/octet-stream")
+			.build()?;
+
+		let response = client.execute(request).await?;
+
+		if response.status().is_success() {
+			Ok(response.bytes().await?.to_vec())
+		} else {
+			let status = response.status();
+			Err(format!("Не удалось загрузить '{}': сервер вернул статус {}", asset.name, status).into())
+		}
+	}
+
+	// Загружает файл релиза в `destination` по частям, показывая ход загрузки.
+	// Если файл уже частично загружен, загрузка продолжается с места остановки.
+	async fn download_asset(&self, asset: &ReleaseAsset, destination: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+		if let UpdateSource::Directory(dir) = self {
+			std::fs::copy(dir.join(&asset.name), destination)?;
+			return Ok(());
+		}
+		let url = self.asset_url(asset)?;
+
+		// Вместо общего ограничения времени — ограничение простоя: медленная, но живая
+		// загрузка не прерывается.
+		let client = reqwest::Client::builder()
+			.connect_timeout(Duration::from_secs(10))
+			.read_timeout(DOWNLOAD_READ_TIMEOUT)
+			.build()?;
+
+		// println!("Загрузка обновления с адреса: {}", url);
+
+		let mut downloaded = std::fs::metadata(destination).map(|meta| meta.len()).unwrap_or(0);
+		let mut request = client
+			.get(&url)
+			.header("User-Agent", USER_AGENT)
+			.header("Accept", "application/octet-stream");
+		if downloaded > 0 {
+			request = request.header("Range", format!("bytes={}-", downloaded));
+		}
+
+		let mut response = client.execute(request.build()?).await?;
+		// println!("Статус ответа загрузки: {}", response.status());
+
+		let mut file = match response.status() {
+			reqwest::StatusCode::PARTIAL_CONTENT => {
+				println!("Продолжение загрузки с {:.1} МБ", megabytes(downloaded));
+				OpenOptions::new().append(true).open(destination)?
+			}
+			// Сервер не поддерживает докачку — начинаем заново.
+			status if status.is_success() => {
+				downloaded = 0;
+				File::create(destination)?
 			}
-			_ => {
-				// Если разбор не удался, перейти к сравнению строк.
-				return remote > current;
+			// Запрошенный диапазон за концом файла: файл уже загружен полностью.
+			reqwest::StatusCode::RANGE_NOT_SATISFIABLE => return Ok(()),
+			status => {
+				return Err(format!("Не удалось загрузить '{}': сервер вернул статус {}", asset.name, status).into());
 			}
+		};
+
+		let total = response.content_length().map(|length| length + downloaded);
+		let mut shown = None;
+		loop {
+			let chunk = match response.chunk().await {
+				Ok(Some(chunk)) => chunk,
+				Ok(None) => break,
+				Err(e) => {
+					println!();
+					return Err(format!("Загрузка прервана ({}). При следующей попытке она продолжится с места остановки.", e).into());
+				}
+			};
+			file.write_all(&chunk)?;
+			downloaded += chunk.len() as u64;
+			shown = print_progress(downloaded, total, shown);
 		}
+		println!();
+		Ok(())
 	}
+}
 
-	// Если все детали сравниваются как равные, пульт дистанционного управления не является более новым.
-	false
+// Переводит байты в мегабайты для вывода.
+fn megabytes(bytes: u64) -> f64 {
+	bytes as f64 / (1024.0 * 1024.0)
 }
 
-pub async fn download_update(remote_version: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	let client = reqwest::Client::builder()
-		.timeout(Duration::from_secs(30)) // Увеличить время ожидания при загрузке файла
-		.build()?;
+// Показывает ход загрузки в одной строке консоли. Строка перерисовывается только при
+// изменении процента (или каждые 0,1 МБ, если размер файла неизвестен).
+fn print_progress(downloaded: u64, total: Option<u64>, shown: Option<u64>) -> Option<u64> {
+	let step = match total {
+		Some(total) if total > 0 => downloaded * 100 / total,
+		_ => downloaded / (100 * 1024),
+	};
+	if shown == Some(step) {
+		return shown;
+	}
 
-	let download_url = format!("{}/{}/{}", DOWNLOAD_URL, remote_version, APP_NAME);
-	// println!("Загрузка обновления с адреса: {}", download_url);
+	match total {
+		Some(total) if total > 0 => {
+			let filled = (downloaded * PROGRESS_WIDTH as u64 / total).min(PROGRESS_WIDTH as u64) as usize;
+			print!(
+				"\rЗагрузка: [{}{}] {:>3}% ({:.1} из {:.1} МБ)",
+				"#".repeat(filled),
+				"-".repeat(PROGRESS_WIDTH - filled),
+				step,
+				megabytes(downloaded),
+				megabytes(total)
+			);
+		}
+		_ => print!("\rЗагрузка: {:.1} МБ", megabytes(downloaded)),
+	}
+	let _ = io::stdout().flush();
+	Some(step)
+}
 
-	let request = client
-		.get(&download_url)
-		.header("User-Agent", USER_AGENT)
-		.header("Accept", "application/octet-stream")
-		.build()?;
+// Показывает описание изменений нового релиза.
+fn print_release_notes(release: &Release) {
+	let notes = release.body.as_deref().map(str::trim).unwrap_or_default();
+	if notes.is_empty() {
+		return;
+	}
+	println!("\nЧто нового в версии {}:", release.version());
+	for line in notes.lines() {
+		println!("  {}", line.trim_end());
+	}
+	println!();
+}
 
-	let response = client.execute(request).await?;
-	// println!("Статус ответа загрузки: {}", response.status());
+// Рантайм tokio создаётся только на время сетевых операций.
+fn runtime() -> std::io::Result<tokio::runtime::Runtime> {
+	tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+}
 
-	if response.status().is_success() {
-		let bytes = response.bytes().await?;
-		// println!("Загружено {} байт", bytes.len());
+// Синхронная обёртка над check_for_update.
+pub fn check_for_update_blocking(settings: &UpdateSettings) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	runtime()?.block_on(check_for_update(settings))
+}
 
-		// Проверить, получили ли мы данные.
-		if bytes.is_empty() {
-			return Err("Загружен пустой файл обновления".into());
+pub async fn check_for_update(settings: &UpdateSettings) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	let release = match find_update(settings).await {
+		Ok(Some(release)) => release,
+		Ok(None) => {
+			// println!("Установлена последняя версия: {}", CURRENT_VERSION);
+			return Ok(());
 		}
+		Err(e) => return Err(format!("Не удалось проверить обновления: {}", e).into()),
+	};
+
+	println!("Доступна новая версия: {} (текущая: {})", release.version(), CURRENT_VERSION);
+	print_release_notes(&release);
+
+	// Загрузить и установить обновление.
+	if install_update(&settings.source, &release).await? {
+		println!("Обновление успешно установлено! Запуск новой версии...");
+		// Запустить новую версию и дождаться её завершения: если выйти сразу, оболочка
+		// заберет терминал, и новая версия будет остановлена при первом чтении ввода.
+		let status = confirm_new_version(UPDATED_FLAG)?.wait()?;
+		std::process::exit(status.code().unwrap_or(1));
+	}
+	Ok(())
+}
+
+// Ищет релиз новее текущей версии.
+async fn find_update(settings: &UpdateSettings) -> Result<Option<Release>, Box<dyn std::error::Error + Send + Sync>> {
+	let release = settings.source.fetch_latest_release(settings.channel).await?;
+	// Используйте правильное сравнение версий.
+	Ok(is_newer_version(release.version(), CURRENT_VERSION).then_some(release))
+}
 
-		// Сохраните файл с суффиксом _new.
-		std::fs::write(NEW_APP_NAME, &bytes)?;
-		// println!("Обновление успешно загружено как '{}'", NEW_APP_NAME);
+// Загружает и устанавливает релиз. Возвращает true, если новая версия встала на место текущей.
+async fn install_update(source: &UpdateSource, release: &Release) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+	// Ошибка загрузки или проверки подписи возвращается вызывающему как есть: установка не удалась.
+	let staged = download_update(source, release).await?;
+
+	// println!("Обновление успешно загружено!");
+	// Применить обновление. apply_update сам возвращает текущую версию на место при ошибке.
+	if apply_update(&staged, release.version())? {
+		return Ok(true);
+	}
+	println!("Не удалось установить обновление. Файл сохранен как '{}'", staged.display());
+	Ok(false)
+}
+
+/// Проверка обновлений, запущенная в фоновом потоке.
+pub struct BackgroundCheck {
+	source: UpdateSource,
+	receiver: mpsc::Receiver<Option<Release>>,
+}
 
+/**
+ * Запускает проверку обновлений в отдельном потоке, чтобы не задерживать ввод.
+ * Возвращает None, если автоматическая проверка отключена или уже выполнялась за последние сутки.
+ */
+pub fn spawn_background_check(settings: UpdateSettings) -> Option<BackgroundCheck> {
+	if !settings.auto_check || !is_check_due() {
+		return None;
+	}
+
+	let (sender, receiver) = mpsc::channel();
+	let source = settings.source.clone();
+	std::thread::spawn(move || {
+		// Ошибки фоновой проверки (например, нет сети) не показываются пользователю.
+		let release = match runtime().map(|runtime| runtime.block_on(find_update(&settings))) {
+			Ok(Ok(release)) => {
+				record_check_time();
+				release
+			}
+			_ => None,
+		};
+		let _ = sender.send(release);
+	});
+
+	Some(BackgroundCheck { source, receiver })
+}
+
+impl BackgroundCheck {
+	/// Предлагает установить обновление, если проверка нашла его.
+	/// Незавершённая проверка ожидается не дольше нескольких секунд.
+	pub fn offer_update(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+		let Ok(Some(release)) = self.receiver.recv_timeout(BACKGROUND_CHECK_GRACE) else {
+			return Ok(());
+		};
+
+		println!("\nДоступна новая версия: {} (текущая: {})", release.version(), CURRENT_VERSION);
+		print_release_notes(&release);
+		if !crate::io_handler::prompt_confirmation("Загрузить и установить обновление?")? {
+			return Ok(());
+		}
+
+		if runtime()?.block_on(install_update(&self.source, &release))? {
+			confirm_new_version(HEALTH_CHECK_FLAG)?.wait()?;
+			println!("Обновление успешно установлено и проверено! Новая версия будет использована при следующем запуске.");
+		}
 		Ok(())
-	} else {
-		let status = response.status();
-		// Не пытайтесь прочитать текст ответа, если это 404, так как это может вызвать другую ошибку.
-		if status == reqwest::StatusCode::NOT_FOUND {
-			Err(format!("Файл обновления не найден на сервере ({}). Возможно, релиз еще не создан.", status).into())
-		} else {
-			let error_text = response.text().await.unwrap_or_else(|_| "Нет текста ошибки".to_string());
-			Err(format!("Ошибка вернула статус: {} - {}", status, error_text).into())
+	}
+}
+
+// Путь к файлу с временем последней автоматической проверки.
+fn last_check_path() -> Option<PathBuf> {
+	Some(paths::ensure(&paths::dirs().state).ok()?.join(LAST_CHECK_FILE_NAME))
+}
+
+fn unix_now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+// Пора ли снова проверять обновления.
+fn is_check_due() -> bool {
+	let last_check = last_check_path()
+		.and_then(|path| std::fs::read_to_string(path).ok())
+		.and_then(|content| content.trim().parse::<u64>().ok());
+	match last_check {
+		Some(last_check) => unix_now().saturating_sub(last_check) >= CHECK_INTERVAL_SECS,
+		None => true,
+	}
+}
+
+// Запоминает время успешной проверки; ошибки записи не мешают работе.
+fn record_check_time() {
+	if let Some(path) = last_check_path() {
+		let _ = std::fs::write(path, unix_now().to_string());
+	}
+}
+
+// Вспомогательная функция для сравнения версий по правилам SemVer.
+// Версии, которые не удалось разобрать, не считаются новее.
+fn is_newer_version(remote: &str, current: &str) -> bool {
+	match (Version::parse(remote), Version::parse(current)) {
+		(Some(remote), Some(current)) => remote > current,
+		_ => false,
+	}
+}
+
+// Загружает и проверяет релиз. Возвращает путь к подготовленному файлу в кэше.
+async fn download_update(source: &UpdateSource, release: &Release) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+	// Старые релизы содержат только MafiaGameGenerator.exe для Windows.
+	let asset_name = release_asset_name();
+	let asset = release
+		.asset(&asset_name)
+		.or_else(|| if cfg!(windows) { release.asset(LEGACY_WINDOWS_ASSET) } else { None })
+		.ok_or_else(|| format!("Релиз {} не содержит сборку для этой системы ('{}')", release.tag_name, asset_name))?;
+
+	// Загрузка идёт во временный файл в кэше; имя содержит версию,
+	// чтобы не продолжить загрузку другого релиза.
+	let cache_dir = paths::ensure(&paths::dirs().cache)?;
+	let staged_path = cache_dir.join(format!("{}-{}", asset.name, release.version()));
+	let part_path = cache_dir.join(format!("{}-{}.part", asset.name, release.version()));
+
+	source.download_asset(asset, &part_path).await?;
+
+	// Проверить, получили ли мы данные.
+	if std::fs::metadata(&part_path)?.len() == 0 {
+		std::fs::remove_file(&part_path)?;
+		return Err("Загружен пустой файл обновления".into());
+	}
+
+	// Проверить подпись манифеста и контрольную сумму до установки файла.
+	let manifest = fetch_release_text(source, release, CHECKSUMS_ASSET).await?;
+	let signature = fetch_release_text(source, release, SIGNATURE_ASSET).await?;
+	if let Err(e) = verify_update(&sha256_file(&part_path)?, &asset.name, release.version(), &manifest, &signature) {
+		// Повреждённый файл нельзя использовать для докачки.
+		let _ = std::fs::remove_file(&part_path);
+		return Err(e.into());
+	}
+
+	std::fs::rename(&part_path, &staged_path)?;
+	// println!("Обновление успешно загружено как '{}'", staged_path.display());
+
+	Ok(staged_path)
+}
+
+// Вычисляет SHA-256 файла, читая его по частям.
+fn sha256_file(path: &Path) -> io::Result<String> {
+	let mut file = File::open(path)?;
+	let mut hasher = Sha256::new();
+	let mut buffer = [0u8; 64 * 1024];
+	loop {
+		let read = file.read(&mut buffer)?;
+		if read == 0 {
+			break;
 		}
+		hasher.update(&buffer[..read]);
 	}
+	Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+// Загружает текстовый файл релиза (манифест или подпись).
+async fn fetch_release_text(
+	source: &UpdateSource,
+	release: &Release,
+	asset_name: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+	let asset = release.asset(asset_name).ok_or_else(|| {
+		AppError::UpdateVerification(format!("релиз не содержит '{}', обновление нельзя проверить", asset_name))
+	})?;
+	let bytes = source.fetch_asset(asset).await.map_err(|e| {
+		AppError::UpdateVerification(format!("не удалось загрузить '{}' ({}), обновление нельзя проверить", asset_name, e))
+	})?;
+	String::from_utf8(bytes)
+		.map_err(|_| AppError::UpdateVerification(format!("файл '{}' повреждён", asset_name)).into())
+}
+
+// Проверяет подпись манифеста встроенным ключом, сверяет подписанную версию с версией релиза
+// и контрольную сумму загруженного файла. `actual` — SHA-256 загруженного файла в шестнадцатеричном виде.
+// Версия из описания релиза не подписана, поэтому без этой сверки зеркало могло бы выдать
+// старый подписанный релиз за новый.
+fn verify_update(actual: &str, asset_name: &str, version: &str, manifest: &str, signature: &str) -> Result<(), AppError> {
+	let public_key = UPDATE_PUBLIC_KEY.ok_or_else(|| {
+		AppError::UpdateVerification("в эту сборку не встроен ключ подписи релизов, обновление нельзя проверить".to_string())
+	})?;
+	let public_key = PublicKey::from_base64(public_key)
+		.map_err(|e| AppError::UpdateVerification(format!("некорректный публичный ключ: {e}")))?;
+	let signature = Signature::decode(signature)
+		.map_err(|e| AppError::UpdateVerification(format!("некорректная подпись: {e}")))?;
+	public_key
+		.verify(manifest.as_bytes(), &signature, false)
+		.map_err(|_| AppError::UpdateVerification(format!("подпись файла '{}' недействительна", CHECKSUMS_ASSET)))?;
+
+	let signed_version = signature
+		.trusted_comment()
+		.split_whitespace()
+		.find_map(|token| token.strip_prefix(SIGNED_VERSION_KEY))
+		.map(|signed| signed.trim_start_matches('v'));
+	if signed_version != Some(version) {
+		return Err(AppError::UpdateVerification(format!(
+			"подписанная версия ({}) не совпадает с версией релиза {}",
+			signed_version.unwrap_or("не указана"),
+			version
+		)));
+	}
+
+	let expected = find_checksum(manifest, asset_name).ok_or_else(|| {
+		AppError::UpdateVerification(format!("в '{}' нет контрольной суммы для '{}'", CHECKSUMS_ASSET, asset_name))
+	})?;
+	if !expected.eq_ignore_ascii_case(actual) {
+		return Err(AppError::UpdateVerification(format!(
+			"контрольная сумма '{}' не совпадает (ожидалась {}, получена {})",
+			asset_name, expected, actual
+		)));
+	}
+	Ok(())
+}
+
+// Ищет в манифесте строку вида `<sha256>  <имя файла>` (в двоичном режиме перед именем стоит `*`).
+fn find_checksum<'a>(manifest: &'a str, asset_name: &str) -> Option<&'a str> {
+	manifest.lines().find_map(|line| {
+		let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+		let name = name.trim_start();
+		let name = name.strip_prefix('*').unwrap_or(name);
+		(name == asset_name).then_some(hash)
+	})
 }
 
 // Функция для применения обновления путем атомарного переименования файлов.
-fn apply_update() -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-	let current_exe = std::env::current_exe()?;
-	let current_dir = current_exe.parent().ok_or("Не удалось определить директорию приложения")?;
+// Запущенный файл нельзя перезаписать в Windows, но можно переименовать,
+// поэтому текущая версия сначала переносится в резервную копию.
+// Перед заменой записывается журнал: пока новая версия не подтвердит запуск,
+// обновление считается незавершенным и может быть отменено.
+fn apply_update(staged: &Path, new_version: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+	let paths = app_paths()?;
 
 	// println!("Установка обновления...");
 
 	// Проверить, существует ли файл новой версии.
-	let new_file_path = format!("{}/{}", current_dir.to_string_lossy(), NEW_APP_NAME);
-	if !Path::new(&new_file_path).exists() {
-		return Err(format!("Файл обновления '{}' не найден", NEW_APP_NAME).into());
+	if !staged.exists() {
+		return Err(format!("Файл обновления '{}' не найден", staged.display()).into());
 	}
 
-	// Создать резервную копию текущей версии атомарно.
-	let current_file_path = format!("{}/{}", current_dir.to_string_lossy(), APP_NAME);
-	let backup_path = format!("{}/{}", current_dir.to_string_lossy(), BACKUP_APP_NAME);
+	// Кэш может находиться на другом диске, а переименование работает только в пределах
+	// одного, поэтому файл сначала копируется к исполняемому файлу с суффиксом _new.
+	std::fs::copy(staged, &paths.new)?;
+
+	// Убрать резервную копию от прошлого обновления, если она осталась.
+	if paths.backup.exists() {
+		std::fs::remove_file(&paths.backup)?;
+	}
 
-	if Path::new(&current_file_path).exists() {
-		// println!("Создание резервной копии текущей версии...");
-		atomic_copy(&current_file_path, &backup_path)?;
+	make_executable(&paths.new)?;
+
+	let journal = UpdateJournal {
+		executable: paths.current.to_string_lossy().into_owned(),
+		from_version: CURRENT_VERSION.to_string(),
+		to_version: new_version.to_string(),
+		confirmed: false,
+		installed_at: unix_now(),
+	};
+	journal.save()?;
+
+	// Перенести текущую версию в резервную копию.
+	if let Err(e) = std::fs::rename(&paths.current, &paths.backup) {
+		UpdateJournal::remove();
+		return Err(e.into());
 	}
 
-	// Атомарно заменить текущее приложение новой версией.
-	// println!("Установка новой версии...");
-	atomic_replace(&new_file_path, &current_file_path)?;
+	// Установить новую версию на место текущей.
+	if let Err(e) = std::fs::rename(&paths.new, &paths.current) {
+		// Вернуть текущую версию на место.
+		std::fs::rename(&paths.backup, &paths.current)?;
+		UpdateJournal::remove();
+		return Err(e.into());
+	}
 
+	let _ = std::fs::remove_file(staged);
 	Ok(true)
 }
 
-// Функция для выполнения атомарного копирования файла.
-fn atomic_copy(from: &str, to: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	// Создать временное имя файла.
-	let temp_path = format!("{}.tmp", to);
-
-	// Копировать во временный файл.
-	std::fs::copy(from, &temp_path)?;
-
-	// Атомарно переименовать временный файл в целевой.
-	std::fs::rename(&temp_path, to)?;
-
-	Ok(())
+/// Журнал установки обновления. Хранится в каталоге состояния.
+struct UpdateJournal {
+	/// Обновленный исполняемый файл: журнал относится только к нему.
+	executable: String,
+	/// Версия, сохраненная в резервной копии.
+	from_version: String,
+	/// Установленная версия.
+	to_version: String,
+	/// Подтвердила ли новая версия успешный запуск.
+	confirmed: bool,
+	/// Время установки в секундах Unix.
+	installed_at: u64,
 }
 
-// Функция для выполнения атомарной замены файла.
-fn atomic_replace(from: &str, to: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	// Создать временное имя файла.
-	let temp_path = format!("{}.tmp", to);
+impl UpdateJournal {
+	fn path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+		Ok(paths::ensure(&paths::dirs().state)?.join(JOURNAL_FILE_NAME))
+	}
 
-	// Копировать новый файл во временное местоположение.
-	std::fs::copy(from, &temp_path)?;
+	/// Читает журнал. Отсутствующий или поврежденный журнал считается пустым,
+	/// как и журнал другой копии программы.
+	fn load() -> Option<Self> {
+		let content = std::fs::read_to_string(Self::path().ok()?).ok()?;
+		let mut executable = None;
+		let mut from_version = None;
+		let mut to_version = None;
+		let mut confirmed = None;
+		let mut installed_at = None;
+		for line in content.lines() {
+			match line.split_once('=') {
+				Some(("exe", value)) => executable = Some(value.to_string()),
+				Some(("from", value)) => from_version = Some(value.trim().to_string()),
+				Some(("to", value)) => to_version = Some(value.trim().to_string()),
+				Some(("state", "pending")) => confirmed = Some(false),
+				Some(("state", "confirmed")) => confirmed = Some(true),
+				Some(("installed", value)) => installed_at = value.trim().parse().ok(),
+				_ => {}
+			}
+		}
+		let executable = executable?;
+		if executable != app_paths().ok()?.current.to_string_lossy() {
+			return None;
+		}
+		Some(UpdateJournal {
+			executable,
+			from_version: from_version?,
+			to_version: to_version?,
+			confirmed: confirmed?,
+			// В журнале прежних версий времени нет: такое обновление давно не новое.
+			installed_at: installed_at.unwrap_or_default(),
+		})
+	}
 
-	// Атомарно переименовать временный файл в целевой, заменив исходный.
-	std::fs::rename(&temp_path, to)?;
+	/// Сколько прошло с установки обновления.
+	fn age(&self) -> Duration {
+		Duration::from_secs(unix_now().saturating_sub(self.installed_at))
+	}
 
-	// Удалить исходный файл (теперь замененный)
-	std::fs::remove_file(from)?;
+	/// Записывает журнал через временный файл, чтобы он не остался записанным наполовину.
+	fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+		let path = Self::path()?;
+		let state = if self.confirmed { "confirmed" } else { "pending" };
+		let content = format!(
+			"exe={}\nfrom={}\nto={}\nstate={}\ninstalled={}\n",
+			self.executable, self.from_version, self.to_version, state, self.installed_at
+		);
+
+		let mut temp_name = path.file_name().ok_or("Неверный путь к журналу обновления")?.to_os_string();
+		temp_name.push(".tmp");
+		let temp_path = path.with_file_name(temp_name);
+		std::fs::write(&temp_path, content)?;
+		std::fs::rename(&temp_path, &path)?;
+		Ok(())
+	}
 
-	Ok(())
+	fn remove() {
+		if let Ok(path) = Self::path() {
+			let _ = std::fs::remove_file(path);
+		}
+	}
 }
 
-// Функция запуска новой версии.
-fn launch_new_version() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	let current_exe = std::env::current_exe()?;
-	let current_dir = current_exe.parent().ok_or("Не удалось определить директорию приложения")?;
+// Запускает установленную версию с флагом `flag` и ждет, пока она подтвердит
+// успешный запуск. Если версия завершилась или не ответила вовремя, обновление откатывается.
+// Возвращает запущенный процесс: вызывающий обязан дождаться его завершения,
+// так как блокировка единственного экземпляра передана ему (см. `instance::hand_over`).
+fn confirm_new_version(flag: &str) -> Result<Child, Box<dyn std::error::Error + Send + Sync>> {
+	let paths = app_paths()?;
+	let mut child = instance::hand_over(&mut Command::new(&paths.current)).arg(flag).spawn()?;
+	let deadline = Instant::now() + CONFIRM_TIMEOUT;
+
+	loop {
+		// Проверить завершение до чтения журнала: процесс мог подтвердить запуск и сразу выйти.
+		let exited = child.try_wait()?.is_some();
+		if UpdateJournal::load().is_some_and(|journal| journal.confirmed) {
+			return Ok(child);
+		}
+		if exited {
+			break;
+		}
+		if Instant::now() >= deadline {
+			let _ = child.kill();
+			let _ = child.wait();
+			break;
+		}
+		std::thread::sleep(CONFIRM_POLL_INTERVAL);
+	}
+
+	rollback_update()?;
+	Err(format!("Новая версия не прошла самопроверку. Восстановлена версия {}", CURRENT_VERSION).into())
+}
 
-	// Запустить новую версию с флагом, указывающим, что это перезапуск после обновления.
-	let _new_process = Command::new(format!("{}/{}", current_dir.to_string_lossy(), APP_NAME))
-		.arg("--updated")
-		.spawn()?;
+// Минимальная самопроверка новой версии: генерация ролей должна работать.
+fn health_check() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	use mafia_game_generator::{get_roles_for_players, GameConfig, GameMode};
 
-	println!("Новая версия запущена. Закрытие текущего процесса...");
+	for mode in [GameMode::Classic, GameMode::Extended] {
+		let roles = get_roles_for_players(&GameConfig::new(10, mode))?;
+		if roles.len() != 10 {
+			return Err("Неверное количество сгенерированных ролей".into());
+		}
+	}
 	Ok(())
 }
 
-// Функция очистки для удаления резервной копии после успешного обновления.
-pub fn cleanup_old_version() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	// Проверьте, было ли приложение запущено с флагом --updated.
-	let args: Vec<String> = std::env::args().collect();
-	if args.len() > 1 && args[1] == "--updated" && Path::new(BACKUP_APP_NAME).exists() {
-		// println!("Удаление резервной копии старой версии...");
-		std::fs::remove_file(BACKUP_APP_NAME)?;
+/**
+ * Завершает обновление, начатое предыдущим запуском.
+ * Новая версия, запущенная с флагом `--updated` или `--health-check`, проходит самопроверку
+ * и подтверждает обновление в журнале. Если же программа запущена обычным образом,
+ * а обновление так и не было подтверждено, восстанавливается предыдущая версия.
+ * Пока новая версия может еще проходить самопроверку (журнал моложе `CONFIRM_TIMEOUT`),
+ * обновление не откатывается.
+ */
+pub fn finish_pending_update() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	let flag = env::args().nth(1);
+	let launched_after_update = matches!(flag.as_deref(), Some(UPDATED_FLAG | HEALTH_CHECK_FLAG));
+
+	if let Some(mut journal) = UpdateJournal::load().filter(|journal| !journal.confirmed) {
+		if launched_after_update {
+			if let Err(e) = health_check() {
+				eprintln!("Самопроверка новой версии не пройдена: {}", e);
+				std::process::exit(1);
+			}
+			journal.confirmed = true;
+			journal.save()?;
+		} else if journal.age() >= CONFIRM_TIMEOUT {
+			eprintln!(
+				"Обновление до версии {} не было завершено. Восстановление версии {}...",
+				journal.to_version, journal.from_version
+			);
+			rollback_update()?;
+			// Продолжить работу уже в восстановленной версии.
+			let paths = app_paths()?;
+			let status = instance::hand_over(&mut Command::new(&paths.current)).args(env::args_os().skip(1)).status()?;
+			std::process::exit(status.code().unwrap_or(1));
+		}
 	}
 
-	// Очистить файл блокировки, если он существует.
-	if Path::new(LOCK_FILE_NAME).exists() {
-		let _ = std::fs::remove_file(LOCK_FILE_NAME);
+	if flag.as_deref() == Some(HEALTH_CHECK_FLAG) {
+		std::process::exit(0);
 	}
-
 	Ok(())
 }
 
-// Функция отката в случае сбоя обновления.
+/**
+ * Возвращает версию из резервной копии, сделанной при последнем обновлении.
+ * Текущий файл переносится на место загруженной версии: запущенный файл в Windows
+ * нельзя удалить, но можно переименовать.
+ */
 pub fn rollback_update() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-	let current_exe = std::env::current_exe()?;
-	let current_dir = current_exe.parent().ok_or("Не удалось определить директорию приложения")?;
-
-	let backup_path = format!("{}/{}", current_dir.to_string_lossy(), BACKUP_APP_NAME);
-	let current_path = format!("{}/{}", current_dir.to_string_lossy(), APP_NAME);
+	let paths = app_paths()?;
+	if !paths.backup.exists() {
+		return Err("Резервная копия предыдущей версии не найдена".into());
+	}
 
-	if Path::new(&backup_path).exists() && Path::new(&current_path).exists() {
-		println!("Откат обновления...");
-		// Удалить неудачную новую версию.
-		std::fs::remove_file(&current_path)?;
-		// Восстановить резервную копию.
-		std::fs::rename(&backup_path, &current_path)?;
-		println!("Откат успешно выполнен. Приложение восстановлено до предыдущей версии.");
+	if paths.new.exists() {
+		std::fs::remove_file(&paths.new)?;
 	}
+	std::fs::rename(&paths.current, &paths.new)?;
+	if let Err(e) = std::fs::rename(&paths.backup, &paths.current) {
+		std::fs::rename(&paths.new, &paths.current)?;
+		return Err(e.into());
+	}
+
+	// Файл отмененной версии может быть занят запущенным процессом; тогда он
+	// будет перезаписан при следующей загрузке обновления.
+	let _ = std::fs::remove_file(&paths.new);
+	UpdateJournal::remove();
 	Ok(())
 }
 
-// Функция для проверки, запущен ли другой экземпляр.
-pub fn is_another_instance_running() -> bool {
-	let lock_file_path = LOCK_FILE_NAME;
-
-	// Попробуйте создать или открыть файл блокировки.
-	match File::create(lock_file_path) {
-		Ok(file) => {
-			// Попробуйте заблокировать файл в эксклюзивном режиме.
-			match file.try_lock_exclusive() {
-				Ok(_) => {
-					// Успешно заблокировано, это первый случай.
-					true
-				}
-				Err(_) => {
-					// Другой пример — запуск и удержание блокировки.
-					false
-				}
-			}
-		}
-		Err(_) => {
-			// Невозможно получить доступ к файлу блокировки, предположительно работает другой экземпляр.
-			false
-		}
+/**
+ * Ручной откат последнего обновления (`--rollback`).
+ */
+pub fn rollback_to_previous_version() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	let previous = UpdateJournal::load().map(|journal| journal.from_version);
+	rollback_update()?;
+	match previous {
+		Some(version) => println!("Восстановлена версия {}.", version),
+		None => println!("Восстановлена предыдущая версия."),
 	}
-}
\ No newline at end of file
+	Ok(())
+}
@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+
+/// Номер версии по правилам Semantic Versioning 2.0.0.
+/// Метаданные сборки (`+...`) отбрасываются: они не влияют на порядок версий.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+	pub major: u64,
+	pub minor: u64,
+	pub patch: u64,
+	/// Идентификаторы пре-релиза, например `beta.2` -> [beta, 2].
+	pub pre: Vec<Identifier>,
+}
+
+/// Идентификатор пре-релиза.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identifier {
+	Numeric(u64),
+	AlphaNumeric(String),
+}
+
+impl Version {
+	/// Разбирает строку версии. Префикс `v` из тегов релизов убирается заранее
+	/// (см. `Release::version`), сама версия его не допускает.
+	pub fn parse(text: &str) -> Option<Self> {
+		let text = text.trim();
+		let text = text.split_once('+').map_or(text, |(version, _build)| version);
+		let (core, pre) = match text.split_once('-') {
+			Some((core, pre)) => (core, Some(pre)),
+			None => (text, None),
+		};
+
+		let mut numbers = core.split('.').map(parse_number);
+		let major = numbers.next()??;
+		let minor = numbers.next()??;
+		let patch = numbers.next()??;
+		if numbers.next().is_some() {
+			return None;
+		}
+
+		let pre = match pre {
+			Some(pre) => pre.split('.').map(Identifier::parse).collect::<Option<Vec<_>>>()?,
+			None => Vec::new(),
+		};
+
+		Some(Version { major, minor, patch, pre })
+	}
+
+	/// Является ли версия пре-релизом (alpha, beta, rc и т.п.).
+	pub fn is_prerelease(&self) -> bool {
+		!self.pre.is_empty()
+	}
+}
+
+impl Identifier {
+	fn parse(text: &str) -> Option<Self> {
+		if text.is_empty() || !text.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+			return None;
+		}
+		if text.chars().all(|c| c.is_ascii_digit()) {
+			parse_number(text).map(Identifier::Numeric)
+		} else {
+			Some(Identifier::AlphaNumeric(text.to_string()))
+		}
+	}
+}
+
+// Числовая часть версии: только цифры и без ведущих нулей.
+fn parse_number(text: &str) -> Option<u64> {
+	if text.is_empty() || !text.chars().all(|c| c.is_ascii_digit()) || (text.len() > 1 && text.starts_with('0')) {
+		return None;
+	}
+	text.parse().ok()
+}
+
+impl Ord for Identifier {
+	fn cmp(&self, other: &Self) -> Ordering {
+		match (self, other) {
+			(Identifier::Numeric(a), Identifier::Numeric(b)) => a.cmp(b),
+			// Числовые идентификаторы всегда младше буквенно-цифровых.
+			(Identifier::Numeric(_), Identifier::AlphaNumeric(_)) => Ordering::Less,
+			(Identifier::AlphaNumeric(_), Identifier::Numeric(_)) => Ordering::Greater,
+			(Identifier::AlphaNumeric(a), Identifier::AlphaNumeric(b)) => a.cmp(b),
+		}
+	}
+}
+
+impl PartialOrd for Identifier {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Version {
+	fn cmp(&self, other: &Self) -> Ordering {
+		(self.major, self.minor, self.patch)
+			.cmp(&(other.major, other.minor, other.patch))
+			.then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+				// Пре-релиз младше релиза с тем же номером: 1.0.0-beta.2 < 1.0.0.
+				(true, true) => Ordering::Equal,
+				(true, false) => Ordering::Greater,
+				(false, true) => Ordering::Less,
+				// Поэлементное сравнение; при равном начале короче — младше.
+				(false, false) => self.pre.cmp(&other.pre),
+			})
+	}
+}
+
+impl PartialOrd for Version {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn version(text: &str) -> Version {
+		Version::parse(text).unwrap_or_else(|| panic!("версия '{text}' должна разбираться"))
+	}
+
+	#[test]
+	fn prerelease_is_older_than_release() {
+		assert!(version("1.0.0-beta.2") < version("1.0.0"));
+	}
+
+	#[test]
+	fn numeric_identifiers_compare_as_numbers() {
+		assert!(version("1.0.0-beta.2") < version("1.0.0-beta.11"));
+	}
+
+	#[test]
+	fn version_parts_compare_as_numbers() {
+		assert!(version("0.10.0") > version("0.9.0"));
+	}
+
+	#[test]
+	fn numeric_identifier_is_older_than_alphanumeric() {
+		assert!(version("1.0.0-1") < version("1.0.0-alpha"));
+		assert!(version("1.0.0-alpha.1") < version("1.0.0-alpha.beta"));
+	}
+
+	#[test]
+	fn build_metadata_is_ignored() {
+		assert_eq!(version("1.0.0+build.5"), version("1.0.0"));
+	}
+
+	#[test]
+	fn rejects_leading_zeros() {
+		assert!(Version::parse("01.0.0").is_none());
+		assert!(Version::parse("1.02.0").is_none());
+		assert!(Version::parse("1.0.0-beta.01").is_none());
+	}
+
+	#[test]
+	fn rejects_v_prefix() {
+		assert!(Version::parse("v1.0.0").is_none());
+	}
+}
@@ -0,0 +1,116 @@
+//! Привязки для WebAssembly: генерация ролей из JavaScript на статической странице.
+//!
+//! Сборка:
+//! `cargo build --lib --release --target wasm32-unknown-unknown --features wasm`,
+//! затем `wasm-bindgen --target web --out-dir web/pkg target/wasm32-unknown-unknown/release/mafia_game_generator.wasm`.
+
+use crate::game_setup::{
+	get_roles_for_players, shuffle_roles, shuffle_roles_with_seed, Assignment, GameConfig, GameMode, MAX_PLAYERS, MIN_PLAYERS,
+};
+use crate::role::Role;
+use wasm_bindgen::prelude::*;
+
+/// Роль одного игрока, возвращаемая в JavaScript.
+#[wasm_bindgen]
+pub struct PlayerRole {
+	name: String,
+	role: Role,
+	seat: usize,
+	teammates: String,
+}
+
+#[wasm_bindgen]
+impl PlayerRole {
+	/// Имя игрока.
+	#[wasm_bindgen(getter)]
+	pub fn name(&self) -> String {
+		self.name.clone()
+	}
+
+	/// Номер места за столом (с единицы).
+	#[wasm_bindgen(getter)]
+	pub fn seat(&self) -> usize {
+		self.seat
+	}
+
+	/// Название роли.
+	#[wasm_bindgen(getter, js_name = roleName)]
+	pub fn role_name(&self) -> String {
+		self.role.get_name().to_string()
+	}
+
+	/// Описание роли.
+	#[wasm_bindgen(getter)]
+	pub fn description(&self) -> String {
+		self.role.get_description().to_string()
+	}
+
+	/// Название стороны, за которую играет роль.
+	#[wasm_bindgen(getter)]
+	pub fn faction(&self) -> String {
+		self.role.get_faction().get_name().to_string()
+	}
+
+	/// Цвет стороны в формате CSS.
+	#[wasm_bindgen(getter, js_name = factionColor)]
+	pub fn faction_color(&self) -> String {
+		self.role.get_faction().get_color().to_string()
+	}
+
+	/// Известные игроку союзники (пустая строка, если их нет).
+	#[wasm_bindgen(getter)]
+	pub fn teammates(&self) -> String {
+		self.teammates.clone()
+	}
+}
+
+/**
+ * Генерирует и распределяет роли.
+ * `mode` — "classic" или "extended". Если список имён пуст, игроки называются "Игрок N".
+ * Поддерживаются столы от `MIN_PLAYERS` до `MAX_PLAYERS` игроков.
+ * Если задано зерно `seed` (в JavaScript — `BigInt`), результат воспроизводим
+ * и совпадает с перемешиванием `mgg_shuffle_roles` из C ABI с тем же зерном.
+ */
+#[wasm_bindgen]
+pub fn generate(player_count: u8, mode: &str, names: Vec<String>, seed: Option<u64>) -> Result<Vec<PlayerRole>, JsError> {
+	let game_mode = match mode {
+		"classic" => GameMode::Classic,
+		"extended" => GameMode::Extended,
+		_ => return Err(JsError::new(&format!("Неверный режим игры: {mode}. Допустимые значения: classic, extended"))),
+	};
+
+	let names = if names.is_empty() {
+		(1..=player_count).map(|i| format!("Игрок {i}")).collect()
+	} else {
+		names
+	};
+	if names.len() != player_count as usize {
+		return Err(JsError::new(&format!(
+			"Количество имен игроков ({}) не соответствует количеству игроков ({})",
+			names.len(),
+			player_count
+		)));
+	}
+
+	let config = GameConfig::new(player_count, game_mode)
+		.with_player_limits(MIN_PLAYERS, MAX_PLAYERS)
+		.map_err(|e| JsError::new(&e.to_string()))?;
+	let mut roles = get_roles_for_players(&config).map_err(|e| JsError::new(&e.to_string()))?;
+	match seed {
+		Some(seed) => shuffle_roles_with_seed(&mut roles, seed),
+		None => shuffle_roles(&mut roles),
+	}
+
+	let assignment = Assignment::new(names, roles);
+	Ok(assignment
+		.players
+		.iter()
+		.enumerate()
+		.map(|(index, (name, role))| PlayerRole {
+			name: name.clone(),
+			role: role.clone(),
+			seat: index + 1,
+			teammates: assignment.describe_allies(index),
+		})
+		.collect())
+}
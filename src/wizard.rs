@@ -0,0 +1,486 @@
+//! Мастер настройки игры для интерактивного режима.
+//!
+//! Шаги: режим игры, количество игроков, состав ролей, игроки, формат вывода и подтверждение.
+//! На любом шаге можно ввести `<` (или «назад»), чтобы вернуться к предыдущему.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use mafia_game_generator::cards::CardFormat;
+use mafia_game_generator::error::AppError;
+use mafia_game_generator::game_setup::{GameMode, OutputFormat};
+use mafia_game_generator::role::{Faction, Role, RoleCounts};
+use crate::config::Config;
+use crate::io_handler::{prompt_answer, validate_player_name, Answer, BACK_COMMAND};
+use crate::paths;
+
+// Файл со списком игроков прошлых игр (в каталоге данных).
+const PLAYERS_FILE_NAME: &str = "players.txt";
+// Сколько имен хранится в списке игроков.
+const MAX_REMEMBERED_PLAYERS: usize = 50;
+
+/// Настройки игры, выбранные в мастере.
+pub struct GameSetup {
+	pub role_counts: RoleCounts,
+	/// Имена игроков в порядке ввода.
+	pub player_names: Vec<String>,
+	/// Формат файлов; `None` — роли показываются по очереди на экране и не сохраняются.
+	pub output_format: Option<OutputFormat>,
+}
+
+/// Шаги мастера в порядке прохождения.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+	Mode,
+	PlayerCount,
+	Roles,
+	Players,
+	Output,
+	Confirm,
+}
+
+/// Куда перейти после шага.
+enum Transition {
+	Next,
+	Back,
+}
+
+impl Step {
+	const COUNT: usize = 6;
+
+	fn number(self) -> usize {
+		self as usize + 1
+	}
+
+	fn next(self) -> Self {
+		match self {
+			Step::Mode => Step::PlayerCount,
+			Step::PlayerCount => Step::Roles,
+			Step::Roles => Step::Players,
+			Step::Players => Step::Output,
+			Step::Output | Step::Confirm => Step::Confirm,
+		}
+	}
+
+	fn previous(self) -> Self {
+		match self {
+			Step::Mode | Step::PlayerCount => Step::Mode,
+			Step::Roles => Step::PlayerCount,
+			Step::Players => Step::Roles,
+			Step::Output => Step::Players,
+			Step::Confirm => Step::Output,
+		}
+	}
+
+	fn title(self) -> &'static str {
+		match self {
+			Step::Mode => "Режим игры",
+			Step::PlayerCount => "Количество игроков",
+			Step::Roles => "Состав ролей",
+			Step::Players => "Игроки",
+			Step::Output => "Формат вывода",
+			Step::Confirm => "Подтверждение",
+		}
+	}
+}
+
+/// Состояние мастера: выбранные значения сохраняются при возврате к предыдущим шагам.
+struct Wizard<'a> {
+	settings: &'a Config,
+	game_mode: GameMode,
+	player_count: Option<u8>,
+	role_counts: Option<RoleCounts>,
+	player_names: Vec<String>,
+	output_format: Option<OutputFormat>,
+}
+
+/**
+ * Проводит ведущего по шагам настройки игры и возвращает выбранные настройки.
+ */
+pub fn run(settings: &Config) -> Result<GameSetup, AppError> {
+	let mut wizard = Wizard {
+		settings,
+		game_mode: settings.game_mode()?,
+		player_count: None,
+		role_counts: None,
+		player_names: Vec::new(),
+		output_format: Some(settings.output_format()?),
+	};
+
+	println!("\n--- Настройка игры ---");
+	println!("На любом шаге введите «{BACK_COMMAND}», чтобы вернуться к предыдущему.");
+
+	let mut step = Step::Mode;
+	loop {
+		println!("\nШаг {} из {}. {}", step.number(), Step::COUNT, step.title());
+		let transition = match step {
+			Step::Mode => wizard.choose_mode()?,
+			Step::PlayerCount => wizard.choose_player_count()?,
+			Step::Roles => wizard.adjust_roles()?,
+			Step::Players => wizard.enter_players()?,
+			Step::Output => wizard.choose_output()?,
+			Step::Confirm => wizard.confirm()?,
+		};
+
+		step = match transition {
+			Transition::Next if step == Step::Confirm => return wizard.finish(),
+			Transition::Next => step.next(),
+			Transition::Back if step == Step::Mode => {
+				println!("Это первый шаг.");
+				step
+			}
+			Transition::Back => step.previous(),
+		};
+	}
+}
+
+/**
+ * Добавляет имена в начало списка игроков прошлых игр.
+ */
+pub fn remember_players(names: &[String]) -> std::io::Result<()> {
+	let mut players: Vec<String> = names.to_vec();
+	let known: HashSet<String> = names.iter().map(|name| name.to_lowercase()).collect();
+	players.extend(load_players().into_iter().filter(|name| !known.contains(&name.to_lowercase())));
+	players.truncate(MAX_REMEMBERED_PLAYERS);
+
+	paths::ensure(&paths::dirs().data)?;
+	std::fs::write(players_path(), players.join("\n") + "\n")
+}
+
+impl Wizard<'_> {
+	fn choose_mode(&mut self) -> Result<Transition, AppError> {
+		println!("  1. {} — Мафия, Дон, Шериф, Доктор и мирные жители", mode_name(GameMode::Classic));
+		println!("  2. {} — дополнительно Маньяк", mode_name(GameMode::Extended));
+
+		loop {
+			let question = format!("Выберите режим [Enter — {}]", mode_name(self.game_mode));
+			let game_mode = match prompt_answer(&question)? {
+				Answer::Back => return Ok(Transition::Back),
+				Answer::Value(value) => match value.as_str() {
+					"" => self.game_mode,
+					"1" => GameMode::Classic,
+					"2" => GameMode::Extended,
+					other => match GameMode::parse(other) {
+						Some(mode) => mode,
+						None => {
+							eprintln!("Ошибка: Введите 1 или 2.");
+							continue;
+						}
+					},
+				},
+			};
+
+			// Предложенный состав зависит от режима, поэтому после смены режима он пересчитывается.
+			if game_mode != self.game_mode {
+				self.role_counts = None;
+			}
+			self.game_mode = game_mode;
+			return Ok(Transition::Next);
+		}
+	}
+
+	fn choose_player_count(&mut self) -> Result<Transition, AppError> {
+		let limits = self.settings.game_config(0, self.game_mode)?;
+
+		loop {
+			let question = match self.player_count {
+				Some(count) => format!("Введите количество игроков ({}-{}) [Enter — {}]", limits.min_players, limits.max_players, count),
+				None => format!("Введите количество игроков ({}-{})", limits.min_players, limits.max_players),
+			};
+			let player_count = match prompt_answer(&question)? {
+				Answer::Back => return Ok(Transition::Back),
+				Answer::Value(value) if value.is_empty() && self.player_count.is_some() => return Ok(Transition::Next),
+				Answer::Value(value) => match value.parse::<u8>() {
+					Ok(count) => count,
+					Err(e) => {
+						eprintln!("{}. Пожалуйста, попробуйте еще раз.", AppError::ParseInt(e));
+						continue;
+					}
+				},
+			};
+
+			if !(limits.min_players..=limits.max_players).contains(&player_count) {
+				eprintln!(
+					"{}\nПожалуйста, попробуйте еще раз.",
+					AppError::InvalidPlayerCount { given: player_count, min: limits.min_players, max: limits.max_players }
+				);
+				continue;
+			}
+
+			if self.player_count != Some(player_count) {
+				self.role_counts = None;
+			}
+			self.player_count = Some(player_count);
+			return Ok(Transition::Next);
+		}
+	}
+
+	fn adjust_roles(&mut self) -> Result<Transition, AppError> {
+		let player_count = self.player_count.unwrap_or_default();
+		let suggested = self.suggested_counts()?;
+		let mut counts = self.role_counts.clone().unwrap_or_else(|| suggested.clone());
+
+		println!("Предложенный состав можно изменить: «номер количество», например «1 3».");
+		println!("«сброс» — вернуть предложенный состав, Enter — принять.");
+
+		loop {
+			let entries = counts.entries();
+			for (index, (role, count)) in entries.iter().enumerate() {
+				println!("  {}. {} {} — {}", index + 1, role.get_icon(), role.get_name(), count);
+			}
+			println!("  Всего ролей: {} из {}", counts.total(), player_count);
+
+			match prompt_answer("Изменение состава")? {
+				Answer::Back => {
+					self.role_counts = Some(counts);
+					return Ok(Transition::Back);
+				}
+				Answer::Value(value) if value.is_empty() => match check_layout(&counts, player_count) {
+					Ok(()) => {
+						self.role_counts = Some(counts);
+						return Ok(Transition::Next);
+					}
+					Err(msg) => eprintln!("Ошибка: {msg}"),
+				},
+				Answer::Value(value) if value.eq_ignore_ascii_case("сброс") => counts = suggested.clone(),
+				Answer::Value(value) => {
+					let mut parts = value.split_whitespace().map(str::parse::<usize>);
+					match (parts.next(), parts.next(), parts.next()) {
+						(Some(Ok(number)), Some(Ok(count)), None) if (1..=entries.len()).contains(&number) => {
+							if count > player_count as usize {
+								eprintln!("Ошибка: Ролей одного вида не может быть больше, чем игроков.");
+							} else {
+								counts.set_count(entries[number - 1].0.clone(), count as u8);
+							}
+						}
+						_ => eprintln!("Ошибка: Введите номер роли (1-{}) и количество через пробел.", entries.len()),
+					}
+				}
+			}
+		}
+	}
+
+	fn enter_players(&mut self) -> Result<Transition, AppError> {
+		let player_count = self.player_count.unwrap_or_default() as usize;
+
+		// Имена, введенные раньше, можно оставить при возврате к этому шагу.
+		if self.player_names.len() == player_count {
+			println!("Игроки: {}", self.player_names.join(", "));
+			match prompt_answer("Enter — оставить список, «з» — ввести заново")? {
+				Answer::Back => return Ok(Transition::Back),
+				Answer::Value(value) if value.is_empty() => return Ok(Transition::Next),
+				Answer::Value(_) => {}
+			}
+		}
+
+		let mut names = Vec::with_capacity(player_count);
+		let mut unique_names = HashSet::with_capacity(player_count);
+
+		// Сначала можно выбрать игроков из списка прошлых игр.
+		let known = load_players();
+		if !known.is_empty() {
+			println!("Игроки прошлых игр:");
+			for (index, name) in known.iter().enumerate() {
+				println!("  {}. {}", index + 1, name);
+			}
+			loop {
+				let answer = prompt_answer("Введите номера игроков через пробел или Enter, чтобы ввести имена вручную")?;
+				let Answer::Value(value) = answer else {
+					return Ok(Transition::Back);
+				};
+				match pick_players(&value, &known, player_count) {
+					Ok(picked) => {
+						for name in picked {
+							unique_names.insert(name.to_lowercase());
+							names.push(name);
+						}
+						break;
+					}
+					Err(msg) => eprintln!("Ошибка: {msg}"),
+				}
+			}
+		}
+
+		while names.len() < player_count {
+			let question = format!("Введите имя для Игрока {}", names.len() + 1);
+			let name = match prompt_answer(&question)? {
+				Answer::Back => return Ok(Transition::Back),
+				Answer::Value(value) => value,
+			};
+			match validate_player_name(&name, &unique_names) {
+				Ok(name) => {
+					unique_names.insert(name.to_lowercase());
+					names.push(name);
+				}
+				Err(e) => eprintln!("{e}\nПожалуйста, попробуйте еще раз."),
+			}
+		}
+
+		self.player_names = names;
+		Ok(Transition::Next)
+	}
+
+	fn choose_output(&mut self) -> Result<Transition, AppError> {
+		let options = [
+			Some(OutputFormat::Text),
+			Some(OutputFormat::Encrypted),
+			Some(OutputFormat::Cards(self.settings.card_options(Some(CardFormat::Html))?)),
+			Some(OutputFormat::Cards(self.settings.card_options(Some(CardFormat::Svg))?)),
+			None,
+		];
+		for (index, format) in options.iter().enumerate() {
+			println!("  {}. {}", index + 1, output_name(format));
+		}
+
+		loop {
+			let question = format!("Выберите формат [Enter — {}]", output_name(&self.output_format));
+			match prompt_answer(&question)? {
+				Answer::Back => return Ok(Transition::Back),
+				Answer::Value(value) if value.is_empty() => return Ok(Transition::Next),
+				Answer::Value(value) => match value.parse::<usize>().ok().and_then(|number| options.get(number.wrapping_sub(1))) {
+					Some(format) => {
+						self.output_format = *format;
+						return Ok(Transition::Next);
+					}
+					None => eprintln!("Ошибка: Введите номер формата (1-{}).", options.len()),
+				},
+			}
+		}
+	}
+
+	fn confirm(&mut self) -> Result<Transition, AppError> {
+		let counts = self.role_counts()?;
+
+		println!("  Режим:   {}", mode_name(self.game_mode));
+		println!("  Игроки:  {}", self.player_names.join(", "));
+		let roles: Vec<String> = counts
+			.entries()
+			.into_iter()
+			.filter(|(_, count)| *count > 0)
+			.map(|(role, count)| format!("{} × {}", role.get_name(), count))
+			.collect();
+		println!("  Роли:    {}", roles.join(", "));
+		println!("  Вывод:   {}", output_name(&self.output_format));
+		let output_dir = match self.output_format {
+			Some(OutputFormat::Cards(_)) => Some(self.settings.output_dir("cards")?),
+			Some(OutputFormat::Text | OutputFormat::Encrypted) => Some(self.settings.output_dir("roles")?),
+			None => None,
+		};
+		if let Some(output_dir) = output_dir {
+			println!("  Папка:   {}", output_dir.display());
+		}
+
+		match prompt_answer("Enter — сгенерировать роли")? {
+			Answer::Back => Ok(Transition::Back),
+			Answer::Value(_) => Ok(Transition::Next),
+		}
+	}
+
+	fn finish(self) -> Result<GameSetup, AppError> {
+		Ok(GameSetup {
+			role_counts: self.role_counts()?,
+			player_names: self.player_names,
+			output_format: self.output_format,
+		})
+	}
+
+	// Состав, предложенный для выбранного режима и количества игроков.
+	fn suggested_counts(&self) -> Result<RoleCounts, AppError> {
+		let config = self.settings.game_config(self.player_count.unwrap_or_default(), self.game_mode)?;
+		Role::get_role_counts(&config)
+	}
+
+	// Состав, принятый ведущим, или предложенный, если ведущий его еще не видел.
+	fn role_counts(&self) -> Result<RoleCounts, AppError> {
+		match &self.role_counts {
+			Some(counts) => Ok(counts.clone()),
+			None => self.suggested_counts(),
+		}
+	}
+}
+
+/**
+ * Проверяет, что состав ролей подходит для игры. Возвращает описание проблемы.
+ */
+pub fn check_layout(counts: &RoleCounts, player_count: u8) -> Result<(), String> {
+	let total = counts.total();
+	if total != player_count as usize {
+		return Err(format!("Ролей ({total}) должно быть столько же, сколько игроков ({player_count})."));
+	}
+
+	let mafia: usize = counts
+		.entries()
+		.iter()
+		.filter(|(role, _)| role.get_faction() == Faction::Mafia)
+		.map(|(_, count)| *count as usize)
+		.sum();
+	if mafia == 0 {
+		return Err("В игре должен быть хотя бы один игрок мафии.".to_string());
+	}
+	if mafia * 2 >= player_count as usize {
+		return Err("Мафия должна быть в меньшинстве, иначе она побеждает сразу.".to_string());
+	}
+	Ok(())
+}
+
+// Разбирает номера игроков из списка прошлых игр.
+fn pick_players(value: &str, known: &[String], player_count: usize) -> Result<Vec<String>, String> {
+	let mut picked: Vec<String> = Vec::new();
+	for part in value.split([' ', ',']).filter(|part| !part.is_empty()) {
+		let name = part
+			.parse::<usize>()
+			.ok()
+			.and_then(|number| known.get(number.wrapping_sub(1)))
+			.ok_or_else(|| format!("Нет игрока с номером {part}."))?;
+		if picked.contains(name) {
+			return Err(format!("Игрок {name} выбран дважды."));
+		}
+		picked.push(name.clone());
+	}
+	if picked.len() > player_count {
+		return Err(format!("Выбрано {} игроков, а в игре участвует {}.", picked.len(), player_count));
+	}
+	Ok(picked)
+}
+
+fn players_path() -> PathBuf {
+	paths::dirs().data.join(PLAYERS_FILE_NAME)
+}
+
+// Читает список игроков прошлых игр. Некорректные имена пропускаются.
+fn load_players() -> Vec<String> {
+	let Ok(content) = std::fs::read_to_string(players_path()) else {
+		return Vec::new();
+	};
+	let mut players = Vec::new();
+	let mut unique_names = HashSet::new();
+	for line in content.lines() {
+		if let Ok(name) = validate_player_name(line, &unique_names) {
+			unique_names.insert(name.to_lowercase());
+			players.push(name);
+		}
+	}
+	players.truncate(MAX_REMEMBERED_PLAYERS);
+	players
+}
+
+/**
+ * Название режима игры для ведущего.
+ */
+pub fn mode_name(game_mode: GameMode) -> &'static str {
+	match game_mode {
+		GameMode::Classic => "Классический",
+		GameMode::Extended => "Расширенный",
+	}
+}
+
+fn output_name(output_format: &Option<OutputFormat>) -> &'static str {
+	match output_format {
+		Some(OutputFormat::Text) => "Текстовые файлы",
+		Some(OutputFormat::Encrypted) => "Зашифрованные файлы (PIN-коды будут показаны каждому игроку лично)",
+		Some(OutputFormat::Cards(options)) => match options.format {
+			CardFormat::Html => "Карточки для печати (HTML)",
+			CardFormat::Svg => "Карточки для печати (SVG)",
+		},
+		None => "Показать роли по очереди на этом экране, без файлов",
+	}
+}